@@ -88,6 +88,7 @@ fn overlapping_scopes2() { // too conservative?
 #[rustfmt::skip]
 #[test]
 fn is_borrow_checker_too_conservative() {
+    #[allow(invalid_reference_casting)]
     fn dummy(s: &String) {
         unsafe {
             let ss = s as *const String as *mut String;
@@ -1,30 +1,44 @@
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+mod bytes;
+pub mod combinator;
+pub mod csv;
+mod delimiter;
+pub mod geometry;
+pub mod ini;
+mod intern;
+mod lending;
+mod memchr;
+mod parallel;
+#[cfg(test)]
+mod property;
+mod quoted;
+mod scanner;
+mod span;
+mod stream;
+mod unicode;
+
+#[cfg(unix)]
+pub use bytes::OsStrTok;
+pub use bytes::{ByteDelimiter, ByteTok, DoubleEndedByteDelimiter};
+pub use delimiter::{Delimiter, DoubleEndedDelimiter};
+pub use intern::{Interner, Symbol};
+pub use lending::{Lend, LendStr, LendingIterator, Lent, Unescaped};
+pub use parallel::par_tokenize;
+pub use quoted::Quoted;
+pub use scanner::{Checkpoint, Expected, Scanner};
+pub use span::{LineCol, Span, Spanned};
+pub use stream::StreamTok;
+pub use unicode::{Boundary, Segments, Words};
+
 #[rustfmt::skip]
 mod warming_up {
-    struct Point {x: f64, y: f64}
-    struct Size {w: f64, h: f64}
-
-    enum Shape {
-        Circle(Point, f64),
-        Rectangle(Point, Size),
-    }
+    use super::geometry::{select, Point, Shape};
     use Shape::*;
 
-    fn compute_area(shape: &Shape) -> f64 {
-        match *shape {
-            Circle(_, radius) => std::f64::consts::PI * radius * radius,
-            Rectangle(_, ref size) => size.w * size.h
-        }
-    }
-
-    fn select<'a, T>(shape: &'a Shape, threshold: f64, r1: &'a T, r2: &'a T) -> &'a T {
-        if compute_area(shape) > threshold {
-            r1
-        } else {
-            r2
-        }
-    }
-    
-    #[cfg(feature = "skip")]
+    // `select` ties only the candidates to 'a, not the shape, so its result
+    // may outlive `shape`.
                                                            // 
     fn select_based_on_unit_circle<'a, T>(                 //-------+ a
         threshold: f64, r1: &'a T, r2: &'a T) -> &'a T {   //       | 
@@ -33,38 +47,319 @@ mod warming_up {
         select(&shape, threshold, r1, r2)                  //   |   |
     }                                                      // --+   |
                                                            // ------+
+
+    #[test]
+    fn unit_circle() {
+        let (above, below) = ("above", "below");
+
+        assert_eq!(select_based_on_unit_circle(3., &above, &below), &"above");
+        assert_eq!(select_based_on_unit_circle(4., &above, &below), &"below");
+    }
 }
 
-pub struct StrTok {
-    remaining: Option<String>,
-    delimiter: String,
+/**
+ * StrTok borrows the haystack and the delimiter with two independent lifetimes.
+ *
+ * Tokens are slices of the haystack, so they only need `'haystack` to stay alive:
+ * the delimiter can be dropped as soon as the tokenizer is done with it.
+ *
+ * Every delimiter separates two tokens, so a haystack with `n` delimiters always
+ * yields `n + 1` tokens: a trailing delimiter produces a final empty token, a lone
+ * delimiter produces two, and an empty haystack produces one.
+ *
+ * The delimiter can be anything implementing [`Delimiter`]: a `str`, a `char`,
+ * a set of `char`s or a `Fn(char) -> bool` predicate. With a
+ * [`DoubleEndedDelimiter`] tokens can also be taken from the back, so `.rev()`
+ * walks the fields right to left without collecting them first.
+ *
+ * [`StrTok::limit`] bounds the number of tokens: the last one is the unsplit
+ * remainder, like `str::splitn` (or `str::rsplitn` when walked with `.rev()`).
+ */
+pub struct StrTok<'haystack, 'delim, D: ?Sized = str> {
+    haystack: &'haystack str,
+    delimiter: &'delim D,
+    cursor: Cursor,
 }
 
-impl StrTok {
-    pub fn new(haystack: String, delimiter: String) -> StrTok {
+impl<'haystack, 'delim, D: Delimiter + ?Sized> StrTok<'haystack, 'delim, D> {
+    pub fn new(haystack: &'haystack str, delimiter: &'delim D) -> Self {
         StrTok {
-            remaining: Some(haystack),
+            haystack,
+            delimiter,
+            cursor: Cursor::new(haystack.len()),
+        }
+    }
+
+    /// Stops after `n` tokens, the last of which is the rest of the haystack.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.cursor.limit(n);
+        self
+    }
+}
+
+impl<'haystack, D: Delimiter + ?Sized> Iterator for StrTok<'haystack, '_, D> {
+    type Item = &'haystack str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let haystack = self.haystack;
+        let delimiter = self.delimiter;
+        let range = self.cursor.next(
+            |from, to| {
+                let m = delimiter.find(&haystack[from..to])?;
+                Some(from + m.start..from + m.end)
+            },
+            |at| haystack[at..].chars().next().map_or(1, char::len_utf8),
+        )?;
+        Some(&haystack[range])
+    }
+}
+
+impl<D: DoubleEndedDelimiter + ?Sized> DoubleEndedIterator for StrTok<'_, '_, D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let haystack = self.haystack;
+        let delimiter = self.delimiter;
+        let range = self.cursor.next_back(|from, to| {
+            let m = delimiter.rfind(&haystack[from..to])?;
+            Some(from + m.start..from + m.end)
+        })?;
+        Some(&haystack[range])
+    }
+}
+
+impl<D: Delimiter + ?Sized> FusedIterator for StrTok<'_, '_, D> {}
+
+/// Byte offsets of a tokenizer into its haystack.
+///
+/// Keeping the state as plain offsets (rather than slices) lets the owned
+/// tokenizer re-borrow its own `String`s on every call.
+#[derive(Debug, Clone, Copy)]
+struct Cursor {
+    /// Where the next token starts.
+    start: usize,
+    /// Where the next delimiter search starts. Only differs from `start` after
+    /// an empty match, which must not be found again at the same position.
+    position: usize,
+    end: usize,
+    finished: bool,
+    /// How many tokens may still be produced, if bounded.
+    limit: Option<usize>,
+}
+
+impl Cursor {
+    fn new(len: usize) -> Self {
+        Cursor {
+            start: 0,
+            position: 0,
+            end: len,
+            finished: false,
+            limit: None,
+        }
+    }
+
+    fn limit(&mut self, n: usize) {
+        self.limit = Some(n);
+        self.finished = n == 0;
+    }
+
+    /// Charges one token against the limit. Returns `false` once the limit is
+    /// used up and the rest of the haystack has to come out as the last token.
+    fn charge(&mut self) -> bool {
+        match &mut self.limit {
+            Some(1) => false,
+            Some(n) => {
+                *n -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn remainder(&mut self) -> Range<usize> {
+        self.finished = true;
+        self.start..self.end
+    }
+
+    /// Returns the range of the next token.
+    ///
+    /// `find` reports the absolute range of the first delimiter match within
+    /// `from..to`, and `width` the length of the element starting at a given
+    /// offset (1 at the very end, to step past it).
+    fn next(
+        &mut self,
+        find: impl FnOnce(usize, usize) -> Option<Range<usize>>,
+        width: impl FnOnce(usize) -> usize,
+    ) -> Option<Range<usize>> {
+        if self.finished {
+            return None;
+        }
+        if !self.charge() {
+            return Some(self.remainder());
+        }
+        let found = if self.position <= self.end {
+            find(self.position, self.end)
+        } else {
+            None
+        };
+        match found {
+            Some(m) => {
+                let token = self.start..m.start;
+                self.start = m.end;
+                self.position = if m.is_empty() {
+                    m.end + width(m.end)
+                } else {
+                    m.end
+                };
+                Some(token)
+            }
+            None => Some(self.remainder()),
+        }
+    }
+
+    /// Returns the range of the last token; `rfind` reports the absolute range
+    /// of the last delimiter match within `from..to`.
+    ///
+    /// Only valid for delimiters that never match the empty string.
+    fn next_back(
+        &mut self,
+        rfind: impl FnOnce(usize, usize) -> Option<Range<usize>>,
+    ) -> Option<Range<usize>> {
+        if self.finished {
+            return None;
+        }
+        if !self.charge() {
+            return Some(self.remainder());
+        }
+        match rfind(self.start, self.end) {
+            Some(m) => {
+                let token = m.end..self.end;
+                self.end = m.start;
+                Some(token)
+            }
+            None => Some(self.remainder()),
+        }
+    }
+}
+
+/**
+ * The owned flavour: a thin wrapper that keeps the haystack and delimiter alive
+ * itself and hands out owned tokens.
+ */
+pub struct OwnedStrTok {
+    haystack: String,
+    delimiter: String,
+    cursor: Cursor,
+}
+
+impl OwnedStrTok {
+    pub fn new(haystack: String, delimiter: String) -> OwnedStrTok {
+        let cursor = Cursor::new(haystack.len());
+        OwnedStrTok {
+            haystack,
             delimiter,
+            cursor,
+        }
+    }
+
+    /// Stops after `n` tokens, the last of which is the rest of the haystack.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.cursor.limit(n);
+        self
+    }
+
+    fn borrowed(&self) -> StrTok<'_, '_> {
+        StrTok {
+            haystack: &self.haystack,
+            delimiter: self.delimiter.as_str(),
+            cursor: self.cursor,
         }
     }
 }
 
-impl Iterator for StrTok {
+impl Iterator for OwnedStrTok {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unimplemented!("Implement the next method for StrTok")
+        let mut tokens = self.borrowed();
+        let token = tokens.next().map(str::to_owned);
+        self.cursor = tokens.cursor;
+        token
     }
 }
 
+impl FusedIterator for OwnedStrTok {}
+
+/**
+ * Prefix scanning.
+ *
+ * With a single reference parameter the elided output lifetime is the
+ * haystack's, so the results can outlive the needle. As soon as a second
+ * reference shows up (`until_str`, `until_any`), elision gives up and the
+ * haystack's lifetime has to be spelled out.
+ *
+ * When the delimiter is missing, the prefix is the whole haystack and the
+ * suffix is empty.
+ */
 pub fn until_char(haystack: &str, c: char) -> &str {
-    todo!()
+    split_once_at(haystack, &c).0
+}
+
+pub fn until_str<'haystack>(haystack: &'haystack str, needle: &str) -> &'haystack str {
+    split_once_at(haystack, needle).0
+}
+
+pub fn until_any<'haystack>(haystack: &'haystack str, chars: &[char]) -> &'haystack str {
+    split_once_at(haystack, chars).0
+}
+
+pub fn until_pred(haystack: &str, pred: impl Fn(char) -> bool) -> &str {
+    split_once_at(haystack, &pred).0
+}
+
+pub fn after_char(haystack: &str, c: char) -> &str {
+    split_once_at(haystack, &c).1
+}
+
+/// The parts before and after the first match of `delimiter`.
+pub fn split_once_at<'haystack, D: Delimiter + ?Sized>(
+    haystack: &'haystack str,
+    delimiter: &D,
+) -> (&'haystack str, &'haystack str) {
+    let mut halves = StrTok::new(haystack, delimiter).limit(2);
+    let before = halves.next().unwrap_or_default();
+    let after = halves.next().unwrap_or_default();
+    (before, after)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Haystack / delimiter pairs every tokenizer in this module must split
+    /// exactly like `str::split`.
+    pub(super) const CASES: &[(&str, &str)] = &[
+        ("Quick brown fox", " "),
+        ("Quick brown fox ", " "),
+        (" ", " "),
+        ("", " "),
+        ("  ", " "),
+        (" Quick  fox", " "),
+        ("a,b,,c,", ","),
+        ("a, b, c", ", "),
+        ("aaa", "aa"),
+        ("서울→뉴욕→파리", "→"),
+        ("", ""),
+        ("abc", ""),
+        ("서울", ""),
+    ];
+
+    #[test]
+    fn cases_agree_with_split() {
+        for &(haystack, delimiter) in CASES {
+            let tokens: Vec<&str> = StrTok::new(haystack, delimiter).collect();
+            assert_eq!(tokens, haystack.split(delimiter).collect::<Vec<_>>());
+        }
+    }
+
     #[test]
     #[ignore]
     fn split() {
@@ -75,43 +370,274 @@ mod tests {
         println!("b = {:?}, a = {:?}", b, a);
     }
 
+    /// The owned-token tests as they were written against the original owned
+    /// `StrTok`; such callers keep working by importing `OwnedStrTok as StrTok`.
+    mod owned {
+        use super::super::OwnedStrTok as StrTok;
+
+        #[test]
+        fn test1() {
+            let haystack = "Quick brown fox";
+            let mut words = StrTok::new(haystack.to_string(), " ".to_string());
+
+            assert_eq!(words.next(), Some("Quick".to_string()));
+            assert_eq!(words.next(), Some("brown".to_string()));
+            assert_eq!(words.next(), Some("fox".to_string()));
+            assert_eq!(words.next(), None);
+        }
+
+        #[test]
+        fn test2() {
+            let haystack = "Quick brown fox ";
+            let mut words = StrTok::new(haystack.to_string(), " ".to_string());
+
+            assert_eq!(words.next(), Some("Quick".to_string()));
+            assert_eq!(words.next(), Some("brown".to_string()));
+            assert_eq!(words.next(), Some("fox".to_string()));
+            assert_eq!(words.next(), Some("".to_string()));
+            assert_eq!(words.next(), None);
+        }
+
+        #[test]
+        fn test3() {
+            let haystack = " ";
+            let mut words = StrTok::new(haystack.to_string(), " ".to_string());
+
+            assert_eq!(words.next(), Some("".to_string()));
+            assert_eq!(words.next(), Some("".to_string()));
+            assert_eq!(words.next(), None);
+        }
+    }
+
     #[test]
-    fn test1() {
-        let haystack = "Quick brown fox";
-        let mut words = StrTok::new(haystack.to_string(), " ".to_string());
+    fn empty_haystack_yields_one_empty_token() {
+        let mut words = StrTok::new("", " ");
 
-        assert_eq!(words.next(), Some("Quick".to_string()));
-        assert_eq!(words.next(), Some("brown".to_string()));
-        assert_eq!(words.next(), Some("fox".to_string()));
+        assert_eq!(words.next(), Some(""));
+        assert_eq!(words.next(), None);
         assert_eq!(words.next(), None);
     }
 
     #[test]
-    #[ignore]
-    fn test2() {
-        let haystack = "Quick brown fox ";
-        let mut words = StrTok::new(haystack.to_string(), " ".to_string());
+    fn leading_and_adjacent_delimiters_yield_empty_tokens() {
+        let words: Vec<&str> = StrTok::new(" Quick  fox", " ").collect();
+
+        assert_eq!(words, ["", "Quick", "", "fox"]);
+    }
+
+    #[test]
+    fn borrowed_and_owned_agree() {
+        for haystack in ["Quick brown fox", "Quick brown fox ", " ", "", "  "] {
+            let borrowed: Vec<&str> = StrTok::new(haystack, " ").collect();
+            let owned: Vec<String> =
+                OwnedStrTok::new(haystack.to_string(), " ".to_string()).collect();
+
+            assert_eq!(borrowed, owned, "haystack: {haystack:?}");
+            assert_eq!(borrowed, haystack.split(' ').collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn borrowed_tokens_outlive_delimiter() {
+        let haystack = "Quick brown fox";
+
+        let words: Vec<&str> = {
+            let delimiter = String::from(" ");
+            StrTok::new(haystack, &delimiter).collect()
+        }; // `delimiter` drops here, the tokens only borrow from `haystack`
+
+        assert_eq!(words, ["Quick", "brown", "fox"]);
+    }
+
+    #[test]
+    fn borrowed_multi_char_delimiter() {
+        let mut fields = StrTok::new("a, b, c", ", ");
+
+        assert_eq!(fields.next(), Some("a"));
+        assert_eq!(fields.next(), Some("b"));
+        assert_eq!(fields.next(), Some("c"));
+        assert_eq!(fields.next(), None);
+    }
+
+    #[test]
+    fn char_delimiter() {
+        let words: Vec<&str> = StrTok::new("Quick brown fox", &' ').collect();
+
+        assert_eq!(words, ["Quick", "brown", "fox"]);
+    }
 
-        assert_eq!(words.next(), Some("Quick".to_string()));
-        assert_eq!(words.next(), Some("brown".to_string()));
-        assert_eq!(words.next(), Some("fox".to_string()));
-        assert_eq!(words.next(), Some("".to_string()));
+    #[test]
+    fn char_set_delimiter() {
+        let fields: Vec<&str> = StrTok::new("a,b;c d", &[',', ';', ' ']).collect();
+        assert_eq!(fields, ["a", "b", "c", "d"]);
+
+        let separators: &[char] = &[',', ';'];
+        let fields: Vec<&str> = StrTok::new("a,b;c d", separators).collect();
+        assert_eq!(fields, ["a", "b", "c d"]);
+    }
+
+    #[test]
+    fn predicate_delimiter() {
+        let is_separator = |c: char| c.is_ascii_punctuation() || c.is_whitespace();
+        let fields: Vec<&str> = StrTok::new("2024-01-02 10:11", &is_separator).collect();
+
+        assert_eq!(fields, ["2024", "01", "02", "10", "11"]);
+    }
+
+    #[test]
+    fn unicode_delimiters() {
+        let fields: Vec<&str> = StrTok::new("서울→뉴욕→파리", &'→').collect();
+        assert_eq!(fields, ["서울", "뉴욕", "파리"]);
+
+        let fields: Vec<&str> = StrTok::new("서울→뉴욕→파리", "→뉴").collect();
+        assert_eq!(fields, ["서울", "욕→파리"]);
+    }
+
+    #[test]
+    fn empty_delimiter_matches_between_chars() {
+        for haystack in ["", "a", "abc", "서울"] {
+            let tokens: Vec<&str> = StrTok::new(haystack, "").collect();
+            assert_eq!(tokens, haystack.split("").collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn reverse_tokens() {
+        let haystack = "GET /index.html HTTP/1.1 200";
+
+        let status = StrTok::new(haystack, &' ').next_back();
+        assert_eq!(status, Some("200"));
+
+        let last_two: Vec<&str> = StrTok::new(haystack, &' ').rev().take(2).collect();
+        assert_eq!(last_two, ["200", "HTTP/1.1"]);
+
+        assert_eq!(StrTok::new(haystack, &' ').nth_back(2), Some("/index.html"));
+    }
+
+    #[test]
+    fn reverse_agrees_with_rsplit() {
+        for haystack in ["Quick brown fox", "Quick brown fox ", " ", "", "  a  "] {
+            let tokens: Vec<&str> = StrTok::new(haystack, &' ').rev().collect();
+            assert_eq!(tokens, haystack.rsplit(' ').collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn interleaved_front_and_back() {
+        let mut words = StrTok::new("a b c d", &' ');
+
+        assert_eq!(words.next(), Some("a"));
+        assert_eq!(words.next_back(), Some("d"));
+        assert_eq!(words.next_back(), Some("c"));
+        assert_eq!(words.next(), Some("b"));
         assert_eq!(words.next(), None);
+        assert_eq!(words.next_back(), None);
     }
 
     #[test]
-    #[ignore]
-    fn test3() {
-        let haystack = " ";
-        let mut words = StrTok::new(haystack.to_string(), " ".to_string());
+    fn interleaved_with_empty_tokens() {
+        let mut words = StrTok::new(" ", &' ');
 
-        assert_eq!(words.next(), Some("".to_string()));
-        assert_eq!(words.next(), Some("".to_string()));
+        assert_eq!(words.next_back(), Some(""));
+        assert_eq!(words.next(), Some(""));
+        assert_eq!(words.next_back(), None);
+
+        let mut words = StrTok::new("a,,b", &[',']);
+
+        assert_eq!(words.next_back(), Some("b"));
+        assert_eq!(words.next(), Some("a"));
+        assert_eq!(words.next_back(), Some(""));
         assert_eq!(words.next(), None);
     }
 
     #[test]
-    #[ignore]
+    fn limit_keeps_the_remainder_unsplit() {
+        let mut pair = StrTok::new("key=value=with=equals", &'=').limit(2);
+
+        assert_eq!(pair.next(), Some("key"));
+        assert_eq!(pair.next(), Some("value=with=equals"));
+        assert_eq!(pair.next(), None);
+
+        let mut pair = StrTok::new("key=value=with=equals", &'=').limit(2).rev();
+
+        assert_eq!(pair.next(), Some("equals"));
+        assert_eq!(pair.next(), Some("key=value=with"));
+        assert_eq!(pair.next(), None);
+    }
+
+    #[test]
+    fn limit_on_owned_tokenizer() {
+        let mut pair =
+            OwnedStrTok::new("key=value=with=equals".to_string(), "=".to_string()).limit(2);
+
+        assert_eq!(pair.next(), Some("key".to_string()));
+        assert_eq!(pair.next(), Some("value=with=equals".to_string()));
+        assert_eq!(pair.next(), None);
+    }
+
+    /// Every haystack of up to four symbols drawn from a small alphabet.
+    pub(super) fn corpus(alphabet: &[&str]) -> Vec<String> {
+        let mut corpus = vec![String::new()];
+        let mut previous = vec![String::new()];
+        for _ in 0..4 {
+            previous = previous
+                .iter()
+                .flat_map(|prefix| {
+                    alphabet
+                        .iter()
+                        .map(move |symbol| format!("{prefix}{symbol}"))
+                })
+                .collect();
+            corpus.extend(previous.iter().cloned());
+        }
+        corpus
+    }
+
+    #[test]
+    fn limit_agrees_with_splitn_on_corpus() {
+        for haystack in corpus(&["a", ",", "é", ";"]) {
+            for n in 0..6 {
+                let tokens: Vec<&str> = StrTok::new(&haystack, &',').limit(n).collect();
+                assert_eq!(
+                    tokens,
+                    haystack.splitn(n, ',').collect::<Vec<_>>(),
+                    "{haystack:?} {n}"
+                );
+
+                let tokens: Vec<&str> = StrTok::new(&haystack, &',').limit(n).rev().collect();
+                assert_eq!(
+                    tokens,
+                    haystack.rsplitn(n, ',').collect::<Vec<_>>(),
+                    "{haystack:?} {n}"
+                );
+
+                let tokens: Vec<&str> = StrTok::new(&haystack, ",;").limit(n).collect();
+                assert_eq!(
+                    tokens,
+                    haystack.splitn(n, ",;").collect::<Vec<_>>(),
+                    "{haystack:?} {n}"
+                );
+
+                let tokens: Vec<&str> = StrTok::new(&haystack, "").limit(n).collect();
+                assert_eq!(
+                    tokens,
+                    haystack.splitn(n, "").collect::<Vec<_>>(),
+                    "{haystack:?} {n}"
+                );
+
+                let owned: Vec<String> = OwnedStrTok::new(haystack.clone(), ",".to_string())
+                    .limit(n)
+                    .collect();
+                assert_eq!(
+                    owned,
+                    haystack.splitn(n, ',').collect::<Vec<_>>(),
+                    "{haystack:?} {n}"
+                );
+            }
+        }
+    }
+
+    #[test]
     fn test4() {
         let haystack = "Quick brown fox ";
         let delim = ' ';
@@ -119,4 +645,49 @@ mod tests {
         let result = until_char(haystack, delim);
         assert_eq!(result, "Quick");
     }
+
+    #[test]
+    fn prefix_outlives_needle() {
+        let haystack = "key: value";
+        let key = {
+            let needle = String::from(": ");
+            until_str(haystack, &needle)
+        };
+
+        assert_eq!(key, "key");
+    }
+
+    #[test]
+    fn prefix_siblings() {
+        let haystack = "GET /index.html?lang=ko HTTP/1.1";
+
+        assert_eq!(until_any(haystack, &['?', ' ']), "GET");
+        assert_eq!(until_pred(haystack, |c| c == '/'), "GET ");
+        assert_eq!(after_char(haystack, '?'), "lang=ko HTTP/1.1");
+        assert_eq!(
+            split_once_at(haystack, " HTTP/"),
+            ("GET /index.html?lang=ko", "1.1")
+        );
+    }
+
+    #[test]
+    fn missing_delimiter() {
+        assert_eq!(until_char("Quick", ' '), "Quick");
+        assert_eq!(after_char("Quick", ' '), "");
+        assert_eq!(split_once_at("", &' '), ("", ""));
+        assert_eq!(split_once_at("Quick ", &' '), ("Quick", ""));
+    }
+
+    #[test]
+    fn chained_prefix_scanning() {
+        let line = "Seoul=1946;New York=1946";
+
+        let first = until_char(line, ';');
+        let rest = after_char(line, ';');
+        let (name, year) = split_once_at(first, &'=');
+
+        assert_eq!((name, year), ("Seoul", "1946"));
+        assert_eq!(until_char(rest, '='), "New York");
+        assert_eq!(after_char(rest, '='), "1946");
+    }
 }
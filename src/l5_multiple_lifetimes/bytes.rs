@@ -0,0 +1,262 @@
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use super::memchr::{memchr, memrchr};
+use super::Cursor;
+
+/**
+ * What `ByteTok` splits on: the `[u8]` counterpart of [`Delimiter`].
+ *
+ * [`Delimiter`]: super::Delimiter
+ */
+pub trait ByteDelimiter {
+    /// Byte range of the first match in `haystack`, if any.
+    fn find(&self, haystack: &[u8]) -> Option<Range<usize>>;
+}
+
+/// The `[u8]` counterpart of [`DoubleEndedDelimiter`]: matches are never empty
+/// and never overlap.
+///
+/// [`DoubleEndedDelimiter`]: super::DoubleEndedDelimiter
+pub trait DoubleEndedByteDelimiter: ByteDelimiter {
+    /// Byte range of the last match in `haystack`, if any.
+    fn rfind(&self, haystack: &[u8]) -> Option<Range<usize>>;
+}
+
+/// A byte sequence. The empty sequence matches between every two bytes.
+impl ByteDelimiter for [u8] {
+    fn find(&self, haystack: &[u8]) -> Option<Range<usize>> {
+        match self {
+            [] => return Some(0..0),
+            &[byte] => return ByteDelimiter::find(&byte, haystack),
+            _ => {}
+        }
+        haystack
+            .windows(self.len())
+            .position(|window| window == self)
+            .map(|start| start..start + self.len())
+    }
+}
+
+impl<const N: usize> ByteDelimiter for [u8; N] {
+    fn find(&self, haystack: &[u8]) -> Option<Range<usize>> {
+        ByteDelimiter::find(&self[..], haystack)
+    }
+}
+
+/// A single byte.
+impl ByteDelimiter for u8 {
+    fn find(&self, haystack: &[u8]) -> Option<Range<usize>> {
+        let start = memchr(*self, haystack)?;
+        Some(start..start + 1)
+    }
+}
+
+impl DoubleEndedByteDelimiter for u8 {
+    fn rfind(&self, haystack: &[u8]) -> Option<Range<usize>> {
+        let start = memrchr(*self, haystack)?;
+        Some(start..start + 1)
+    }
+}
+
+/// Any byte the predicate accepts.
+impl<F> ByteDelimiter for F
+where
+    F: Fn(u8) -> bool,
+{
+    fn find(&self, haystack: &[u8]) -> Option<Range<usize>> {
+        let start = haystack.iter().position(|&b| self(b))?;
+        Some(start..start + 1)
+    }
+}
+
+impl<F> DoubleEndedByteDelimiter for F
+where
+    F: Fn(u8) -> bool,
+{
+    fn rfind(&self, haystack: &[u8]) -> Option<Range<usize>> {
+        let start = haystack.iter().rposition(|&b| self(b))?;
+        Some(start..start + 1)
+    }
+}
+
+/**
+ * `StrTok` for byte slices that need not be valid UTF-8.
+ *
+ * Same design and same semantics: the haystack and the delimiter are borrowed
+ * independently, tokens are `&'haystack [u8]`, and `n` delimiters always yield
+ * `n + 1` tokens.
+ */
+pub struct ByteTok<'haystack, 'delim, D: ?Sized = [u8]> {
+    haystack: &'haystack [u8],
+    delimiter: &'delim D,
+    cursor: Cursor,
+}
+
+impl<'haystack, 'delim, D: ByteDelimiter + ?Sized> ByteTok<'haystack, 'delim, D> {
+    pub fn new(haystack: &'haystack [u8], delimiter: &'delim D) -> Self {
+        ByteTok {
+            haystack,
+            delimiter,
+            cursor: Cursor::new(haystack.len()),
+        }
+    }
+
+    /// Stops after `n` tokens, the last of which is the rest of the haystack.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.cursor.limit(n);
+        self
+    }
+}
+
+impl<'haystack, D: ByteDelimiter + ?Sized> Iterator for ByteTok<'haystack, '_, D> {
+    type Item = &'haystack [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let haystack = self.haystack;
+        let delimiter = self.delimiter;
+        let range = self.cursor.next(
+            |from, to| {
+                let m = delimiter.find(&haystack[from..to])?;
+                Some(from + m.start..from + m.end)
+            },
+            |_| 1,
+        )?;
+        Some(&haystack[range])
+    }
+}
+
+impl<D: DoubleEndedByteDelimiter + ?Sized> DoubleEndedIterator for ByteTok<'_, '_, D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let haystack = self.haystack;
+        let delimiter = self.delimiter;
+        let range = self.cursor.next_back(|from, to| {
+            let m = delimiter.rfind(&haystack[from..to])?;
+            Some(from + m.start..from + m.end)
+        })?;
+        Some(&haystack[range])
+    }
+}
+
+impl<D: ByteDelimiter + ?Sized> FusedIterator for ByteTok<'_, '_, D> {}
+
+/**
+ * Tokenizes an `OsStr` (file names, arguments) by its raw bytes.
+ *
+ * Only on Unix, where an `OsStr` *is* an arbitrary byte string.
+ */
+#[cfg(unix)]
+pub struct OsStrTok<'haystack, 'delim> {
+    tokens: ByteTok<'haystack, 'delim>,
+}
+
+#[cfg(unix)]
+impl<'haystack, 'delim> OsStrTok<'haystack, 'delim> {
+    pub fn new(haystack: &'haystack std::ffi::OsStr, delimiter: &'delim std::ffi::OsStr) -> Self {
+        use std::os::unix::ffi::OsStrExt;
+
+        OsStrTok {
+            tokens: ByteTok::new(haystack.as_bytes(), delimiter.as_bytes()),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<'haystack> Iterator for OsStrTok<'haystack, '_> {
+    type Item = &'haystack std::ffi::OsStr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use std::os::unix::ffi::OsStrExt;
+
+        self.tokens.next().map(std::ffi::OsStr::from_bytes)
+    }
+}
+
+#[cfg(unix)]
+impl FusedIterator for OsStrTok<'_, '_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l5_multiple_lifetimes::tests::CASES;
+    use crate::l5_multiple_lifetimes::StrTok;
+
+    #[test]
+    fn same_tokens_as_str_version() {
+        for &(haystack, delimiter) in CASES {
+            if delimiter.is_empty() && !haystack.is_ascii() {
+                // the empty delimiter matches between bytes here, between chars there
+                continue;
+            }
+            let bytes: Vec<&[u8]> =
+                ByteTok::new(haystack.as_bytes(), delimiter.as_bytes()).collect();
+            let strs: Vec<&[u8]> = StrTok::new(haystack, delimiter)
+                .map(str::as_bytes)
+                .collect();
+
+            assert_eq!(bytes, strs, "{haystack:?} / {delimiter:?}");
+        }
+    }
+
+    #[test]
+    fn same_reverse_tokens_as_str_version() {
+        for &(haystack, delimiter) in CASES {
+            let Ok([delimiter]) = <[u8; 1]>::try_from(delimiter.as_bytes()) else {
+                continue;
+            };
+            let bytes: Vec<&[u8]> = ByteTok::new(haystack.as_bytes(), &delimiter)
+                .rev()
+                .collect();
+            let strs: Vec<&[u8]> = StrTok::new(haystack, &char::from(delimiter))
+                .rev()
+                .map(str::as_bytes)
+                .collect();
+
+            assert_eq!(bytes, strs, "{haystack:?} / {delimiter:?}");
+        }
+    }
+
+    #[test]
+    fn empty_delimiter_matches_between_bytes() {
+        let tokens: Vec<&[u8]> = ByteTok::new("é".as_bytes(), b"").collect();
+
+        assert_eq!(tokens, [&b""[..], b"\xc3", b"\xa9", b""]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let header = b"\xff\xfe:\x00\x01:";
+        let fields: Vec<&[u8]> = ByteTok::new(header, &b':').collect();
+
+        assert_eq!(fields, [&b"\xff\xfe"[..], b"\x00\x01", b""]);
+    }
+
+    #[test]
+    fn predicate_and_limit() {
+        let fields: Vec<&[u8]> = ByteTok::new(b"k=v=w\x00x", &|b: u8| b == b'=' || b == 0)
+            .limit(3)
+            .collect();
+
+        assert_eq!(fields, [&b"k"[..], b"v", b"w\x00x"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn os_str_paths() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = OsStr::from_bytes(b"/tmp/caf\xe9/notes.txt");
+        let components: Vec<&OsStr> = OsStrTok::new(path, OsStr::new("/")).collect();
+
+        assert_eq!(
+            components,
+            [
+                OsStr::new(""),
+                OsStr::new("tmp"),
+                OsStr::from_bytes(b"caf\xe9"),
+                OsStr::new("notes.txt"),
+            ]
+        );
+    }
+}
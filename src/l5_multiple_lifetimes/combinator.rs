@@ -0,0 +1,309 @@
+use std::fmt;
+
+use super::{Delimiter, Span};
+
+/**
+ * A tiny parser-combinator layer.
+ *
+ * A parser is any `Fn(&'a str) -> PResult<'a, O>`: it takes the input and
+ * returns the input it did not consume together with its output. Outputs and
+ * errors borrow the input with the same `'a`, so nothing is copied and an
+ * error can point at exactly where parsing stopped.
+ */
+pub type PResult<'a, O> = Result<(&'a str, O), ParseError<'a>>;
+
+/// Why parsing failed, and the input that was left at that point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError<'a> {
+    pub input: &'a str,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Tag,
+    TakeUntil,
+    TakeWhile1,
+    MapRes,
+    Alt,
+}
+
+impl<'a> ParseError<'a> {
+    fn new(input: &'a str, kind: ErrorKind) -> Self {
+        ParseError { input, kind }
+    }
+
+    /// Where in `source` (the input handed to the outermost parser) the
+    /// error happened. The span is empty: it marks a position.
+    pub fn span(&self, source: &str) -> Span {
+        let at = Span::of(source, self.input).start;
+        Span { start: at, end: at }
+    }
+}
+
+impl fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let excerpt: String = self.input.chars().take(16).collect();
+        write!(f, "{:?} failed at {excerpt:?}", self.kind)
+    }
+}
+
+/// Matches `literal` exactly.
+pub fn tag<'a, 't>(literal: &'t str) -> impl Fn(&'a str) -> PResult<'a, &'a str> + 't {
+    move |input| match input.strip_prefix(literal) {
+        Some(rest) => Ok((rest, &input[..literal.len()])),
+        None => Err(ParseError::new(input, ErrorKind::Tag)),
+    }
+}
+
+/// Takes everything before the first match of `delimiter`, leaving the
+/// delimiter in the input. Fails if the delimiter never shows up.
+pub fn take_until<'a, 'd, D: Delimiter + ?Sized>(
+    delimiter: &'d D,
+) -> impl Fn(&'a str) -> PResult<'a, &'a str> + 'd {
+    move |input| match delimiter.find(input) {
+        Some(m) => Ok((&input[m.start..], &input[..m.start])),
+        None => Err(ParseError::new(input, ErrorKind::TakeUntil)),
+    }
+}
+
+/// Takes the longest non-empty prefix whose `char`s all satisfy `pred`.
+pub fn take_while1<'a>(pred: impl Fn(char) -> bool) -> impl Fn(&'a str) -> PResult<'a, &'a str> {
+    move |input| {
+        let end = input.find(|c| !pred(c)).unwrap_or(input.len());
+        if end == 0 {
+            return Err(ParseError::new(input, ErrorKind::TakeWhile1));
+        }
+        Ok((&input[end..], &input[..end]))
+    }
+}
+
+pub fn map<'a, O, U>(
+    parser: impl Fn(&'a str) -> PResult<'a, O>,
+    f: impl Fn(O) -> U,
+) -> impl Fn(&'a str) -> PResult<'a, U> {
+    move |input| parser(input).map(|(rest, output)| (rest, f(output)))
+}
+
+/// Like [`map`], for conversions that can fail; the error points at the
+/// start of what `parser` consumed.
+pub fn map_res<'a, O, U, E>(
+    parser: impl Fn(&'a str) -> PResult<'a, O>,
+    f: impl Fn(O) -> Result<U, E>,
+) -> impl Fn(&'a str) -> PResult<'a, U> {
+    move |input| {
+        let (rest, output) = parser(input)?;
+        match f(output) {
+            Ok(output) => Ok((rest, output)),
+            Err(_) => Err(ParseError::new(input, ErrorKind::MapRes)),
+        }
+    }
+}
+
+pub fn pair<'a, O1, O2>(
+    first: impl Fn(&'a str) -> PResult<'a, O1>,
+    second: impl Fn(&'a str) -> PResult<'a, O2>,
+) -> impl Fn(&'a str) -> PResult<'a, (O1, O2)> {
+    move |input| {
+        let (rest, a) = first(input)?;
+        let (rest, b) = second(rest)?;
+        Ok((rest, (a, b)))
+    }
+}
+
+/// Runs both, keeping the output of the second.
+pub fn preceded<'a, O1, O2>(
+    first: impl Fn(&'a str) -> PResult<'a, O1>,
+    second: impl Fn(&'a str) -> PResult<'a, O2>,
+) -> impl Fn(&'a str) -> PResult<'a, O2> {
+    map(pair(first, second), |(_, b)| b)
+}
+
+/// Runs both, keeping the output of the first.
+pub fn terminated<'a, O1, O2>(
+    first: impl Fn(&'a str) -> PResult<'a, O1>,
+    second: impl Fn(&'a str) -> PResult<'a, O2>,
+) -> impl Fn(&'a str) -> PResult<'a, O1> {
+    map(pair(first, second), |(a, _)| a)
+}
+
+/// The first parser that succeeds.
+pub fn alt<'a, O>(
+    first: impl Fn(&'a str) -> PResult<'a, O>,
+    second: impl Fn(&'a str) -> PResult<'a, O>,
+) -> impl Fn(&'a str) -> PResult<'a, O> {
+    move |input| {
+        first(input)
+            .or_else(|_| second(input))
+            .map_err(|_| ParseError::new(input, ErrorKind::Alt))
+    }
+}
+
+/// Zero or more; stops at the first failure, or when `parser` stops
+/// consuming input.
+pub fn many<'a, O>(
+    parser: impl Fn(&'a str) -> PResult<'a, O>,
+) -> impl Fn(&'a str) -> PResult<'a, Vec<O>> {
+    move |mut input| {
+        let mut outputs = Vec::new();
+        while let Ok((rest, output)) = parser(input) {
+            if rest.len() == input.len() {
+                break;
+            }
+            outputs.push(output);
+            input = rest;
+        }
+        Ok((input, outputs))
+    }
+}
+
+/// Zero or more `parser`s separated by `separator`. A trailing separator is
+/// left in the input.
+pub fn sep_by<'a, O, S>(
+    parser: impl Fn(&'a str) -> PResult<'a, O>,
+    separator: impl Fn(&'a str) -> PResult<'a, S>,
+) -> impl Fn(&'a str) -> PResult<'a, Vec<O>> {
+    move |input| {
+        let mut outputs = Vec::new();
+        let Ok((mut input, first)) = parser(input) else {
+            return Ok((input, outputs));
+        };
+        outputs.push(first);
+        while let Ok((rest, output)) = separator(input).and_then(|(rest, _)| parser(rest)) {
+            outputs.push(output);
+            input = rest;
+        }
+        Ok((input, outputs))
+    }
+}
+
+/**
+ * Worked example: `City` records, one per line, as `name,year`.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct City<'a> {
+    pub name: &'a str,
+    pub date_founded: u32,
+}
+
+pub fn city<'a>(input: &'a str) -> PResult<'a, City<'a>> {
+    let name = terminated(take_until(&','), tag(","));
+    let year = map_res(take_while1(|c| c.is_ascii_digit()), str::parse::<u32>);
+    let record = pair(name, year);
+
+    map(record, |(name, date_founded)| City { name, date_founded })(input)
+}
+
+pub fn cities<'a>(input: &'a str) -> PResult<'a, Vec<City<'a>>> {
+    let newline = alt(tag("\r\n"), tag("\n"));
+    sep_by(city, newline)(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_and_take_until() {
+        assert_eq!(tag("Seo")("Seoul"), Ok(("ul", "Seo")));
+        assert_eq!(
+            tag("New")("Seoul"),
+            Err(ParseError {
+                input: "Seoul",
+                kind: ErrorKind::Tag
+            })
+        );
+
+        assert_eq!(take_until(&',')("Seoul,1946"), Ok((",1946", "Seoul")));
+        assert_eq!(take_until(", ")("a, b"), Ok((", b", "a")));
+        assert_eq!(
+            take_until(&[';', ','])("Seoul").unwrap_err().kind,
+            ErrorKind::TakeUntil
+        );
+    }
+
+    #[test]
+    fn many_and_sep_by() {
+        let digits = take_while1(|c| c.is_ascii_digit());
+
+        assert_eq!(
+            many(terminated(&digits, tag(" ")))("1 22 3x"),
+            Ok(("3x", vec!["1", "22"]))
+        );
+        assert_eq!(
+            sep_by(&digits, tag(","))("1,22,3,"),
+            Ok((",", vec!["1", "22", "3"]))
+        );
+        assert_eq!(sep_by(&digits, tag(","))("x"), Ok(("x", vec![])));
+    }
+
+    #[test]
+    fn many_stops_without_progress() {
+        let nothing = |input| Ok((input, ()));
+
+        assert_eq!(many(nothing)("abc"), Ok(("abc", vec![])));
+    }
+
+    #[test]
+    fn alt_and_map() {
+        let yes_no = alt(map(tag("yes"), |_| true), map(tag("no"), |_| false));
+
+        assert_eq!(yes_no("yes!"), Ok(("!", true)));
+        assert_eq!(yes_no("no!"), Ok(("!", false)));
+        assert_eq!(yes_no("maybe").unwrap_err().kind, ErrorKind::Alt);
+    }
+
+    #[test]
+    fn parse_city_records() {
+        let input = "Seoul,1946\r\nNew York,1946\nBusan,1949";
+
+        let (rest, parsed) = cities(input).unwrap();
+
+        assert_eq!(rest, "");
+        assert_eq!(
+            parsed,
+            [
+                City {
+                    name: "Seoul",
+                    date_founded: 1946
+                },
+                City {
+                    name: "New York",
+                    date_founded: 1946
+                },
+                City {
+                    name: "Busan",
+                    date_founded: 1949
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn cities_borrow_the_input() {
+        let input = String::from("Seoul,1946");
+        let (_, seoul) = city(&input).unwrap();
+
+        assert_eq!(seoul.name.as_ptr(), input.as_ptr());
+    }
+
+    #[test]
+    fn errors_point_into_the_input() {
+        let input = "Seoul,1946\nNew York,99999999999";
+
+        let error = city(after_newline(input)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::MapRes);
+        assert_eq!(error.input, "99999999999");
+        assert_eq!(error.span(input).line_col(input).line, 2);
+
+        let error = city("Seoul 1946").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::TakeUntil);
+        assert_eq!(error.to_string(), r#"TakeUntil failed at "Seoul 1946""#);
+    }
+
+    fn after_newline(input: &str) -> &str {
+        take_until(&'\n')(input)
+            .map(|(rest, _)| &rest[1..])
+            .unwrap()
+    }
+}
@@ -0,0 +1,248 @@
+//! CSV (RFC 4180) and TSV records, borrowed from the input.
+
+use std::borrow::Cow;
+
+use super::{Quoted, Span, StrTok};
+
+const QUOTE: &[char] = &['"'];
+
+/**
+ * Reads records from CSV or TSV text.
+ *
+ * Records end at a line break (`\n` or `\r\n`) outside quotes; a quoted field
+ * may span lines. A final line break does not start an empty record. An
+ * unterminated quote runs to the end of the input.
+ *
+ * Nothing is parsed up front: a [`Record`] is just the record's text, and
+ * fields are split out of it when asked for.
+ */
+pub struct Reader<'a> {
+    input: &'a str,
+    lines: StrTok<'a, 'static, char>,
+    delimiter: char,
+    headers: Option<&'a str>,
+}
+
+/// One record: a slice of the input, plus the header record (if any) to look
+/// fields up by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record<'a> {
+    raw: &'a str,
+    headers: Option<&'a str>,
+    delimiter: char,
+}
+
+impl<'a> Reader<'a> {
+    /// A reader for comma-separated records without a header row.
+    pub fn new(input: &'a str) -> Self {
+        Reader {
+            input,
+            lines: StrTok::new(input, &'\n'),
+            delimiter: ',',
+            headers: None,
+        }
+    }
+
+    /// Separates fields with `delimiter` instead, `'\t'` for TSV.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Takes the next record as the header row, so that the records after it
+    /// can be looked up by field name.
+    pub fn with_headers(mut self) -> Self {
+        self.headers = self.next_raw();
+        self
+    }
+
+    pub fn headers(&self) -> Option<Record<'a>> {
+        self.headers.map(|raw| Record {
+            raw,
+            headers: None,
+            delimiter: self.delimiter,
+        })
+    }
+
+    /// The text of the next record, without its line break.
+    fn next_raw(&mut self) -> Option<&'a str> {
+        let first = self.lines.next()?;
+        let Span { start, mut end } = Span::of(self.input, first);
+        if first.is_empty() && end == self.input.len() {
+            return None;
+        }
+        // Quotes come in pairs, `""` escapes included, so a line break is
+        // inside a quoted field exactly when an odd number of quotes precede it.
+        let mut quotes = first.matches('"').count();
+        while quotes % 2 == 1 {
+            let Some(line) = self.lines.next() else {
+                break;
+            };
+            quotes += line.matches('"').count();
+            end = Span::of(self.input, line).end;
+        }
+        let raw = &self.input[start..end];
+        Some(raw.strip_suffix('\r').unwrap_or(raw))
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Record<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw = self.next_raw()?;
+        Some(Record {
+            raw,
+            headers: self.headers,
+            delimiter: self.delimiter,
+        })
+    }
+}
+
+impl<'a> Record<'a> {
+    /// The record as it appears in the input.
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    /// The fields, unquoted. A field is borrowed from the input unless it
+    /// contains an escaped (`""`) quote.
+    pub fn fields(&self) -> Quoted<'a, '_, 'static, char> {
+        StrTok::new(self.raw, &self.delimiter)
+            .quoted(QUOTE)
+            .escape(None)
+            .doubled_quotes(true)
+    }
+
+    pub fn field(&self, index: usize) -> Option<Cow<'a, str>> {
+        self.fields().nth(index)
+    }
+
+    /// The field under the header `name`, if the reader has headers and one of
+    /// them is `name`.
+    pub fn get(&self, name: &str) -> Option<Cow<'a, str>> {
+        let headers = Record {
+            raw: self.headers?,
+            headers: None,
+            delimiter: self.delimiter,
+        };
+        let index = headers.fields().position(|header| header == name)?;
+        self.field(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Vec<Vec<Cow<'_, str>>> {
+        Reader::new(input)
+            .map(|record| record.fields().collect())
+            .collect()
+    }
+
+    // The examples of RFC 4180, section 2.
+
+    #[test]
+    fn rfc4180_line_breaks() {
+        let expected = [["aaa", "bbb", "ccc"], ["zzz", "yyy", "xxx"]];
+
+        assert_eq!(parse("aaa,bbb,ccc\r\nzzz,yyy,xxx\r\n"), expected);
+        assert_eq!(parse("aaa,bbb,ccc\r\nzzz,yyy,xxx"), expected);
+        assert_eq!(parse("aaa,bbb,ccc\nzzz,yyy,xxx\n"), expected);
+    }
+
+    #[test]
+    fn rfc4180_header() {
+        let input = "field_name,field_name,field_name\r\naaa,bbb,ccc\r\nzzz,yyy,xxx\r\n";
+        let reader = Reader::new(input).with_headers();
+
+        let headers: Vec<Cow<'_, str>> = reader.headers().unwrap().fields().collect();
+        assert_eq!(headers, ["field_name"; 3]);
+        assert_eq!(reader.count(), 2);
+    }
+
+    #[test]
+    fn rfc4180_spaces_are_part_of_a_field() {
+        assert_eq!(parse("aaa, bbb ,ccc"), [["aaa", " bbb ", "ccc"]]);
+    }
+
+    #[test]
+    fn rfc4180_quoted_fields() {
+        assert_eq!(
+            parse("\"aaa\",\"bbb\",\"ccc\"\r\nzzz,yyy,xxx"),
+            [["aaa", "bbb", "ccc"], ["zzz", "yyy", "xxx"]]
+        );
+    }
+
+    #[test]
+    fn rfc4180_line_break_in_quotes() {
+        assert_eq!(
+            parse("\"aaa\",\"b\r\nbb\",\"ccc\"\r\nzzz,yyy,xxx"),
+            [["aaa", "b\r\nbb", "ccc"], ["zzz", "yyy", "xxx"]]
+        );
+    }
+
+    #[test]
+    fn rfc4180_escaped_quotes() {
+        assert_eq!(
+            parse("\"aaa\",\"b\"\"bb\",\"ccc\""),
+            [["aaa", "b\"bb", "ccc"]]
+        );
+    }
+
+    #[test]
+    fn empty_fields_and_input() {
+        assert_eq!(parse(",,\"\""), [["", "", ""]]);
+        assert_eq!(parse(""), Vec::<Vec<Cow<'_, str>>>::new());
+        assert_eq!(parse("\n"), [[""]]);
+    }
+
+    #[test]
+    fn fields_by_name() {
+        let input = String::from("city,founded\r\nSeoul,1946\r\n\"New York, NY\",1624\r\n");
+        let mut records = Reader::new(&input).with_headers();
+
+        let seoul = records.next().unwrap();
+        assert_eq!(seoul.get("city").as_deref(), Some("Seoul"));
+        assert_eq!(seoul.get("founded").as_deref(), Some("1946"));
+        assert_eq!(seoul.get("country"), None);
+
+        let new_york = records.next().unwrap();
+        assert_eq!(new_york.get("city").as_deref(), Some("New York, NY"));
+        assert_eq!(new_york.raw(), "\"New York, NY\",1624");
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn fields_borrow_the_input() {
+        let input = String::from("\"quoted\",plain,\"esc\"\"aped\"");
+        let record = Reader::new(&input).next().unwrap();
+
+        let quoted = record.field(0).unwrap();
+        assert!(matches!(quoted, Cow::Borrowed(_)));
+        assert_eq!(quoted.as_ptr(), input[1..].as_ptr());
+        assert!(matches!(record.field(1), Some(Cow::Borrowed("plain"))));
+        assert!(matches!(record.field(2), Some(Cow::Owned(_))));
+        assert_eq!(record.field(3), None);
+    }
+
+    #[test]
+    fn tab_separated() {
+        let input = "name\tmotto\nSeoul\t\"Seoul, my soul\"\n";
+        let record = Reader::new(input)
+            .delimiter('\t')
+            .with_headers()
+            .next()
+            .unwrap();
+
+        assert_eq!(record.get("motto").as_deref(), Some("Seoul, my soul"));
+    }
+
+    #[test]
+    fn records_without_headers_have_no_names() {
+        let record = Reader::new("a,b").next().unwrap();
+
+        assert_eq!(record.get("a"), None);
+    }
+}
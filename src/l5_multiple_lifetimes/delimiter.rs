@@ -0,0 +1,130 @@
+use std::ops::Range;
+
+use super::memchr::{memchr, memrchr};
+
+/**
+ * What `StrTok` splits on.
+ *
+ * A delimiter only has to report where it first matches; the tokenizer takes
+ * care of slicing the haystack. The returned range must lie on `char`
+ * boundaries of `haystack`.
+ */
+pub trait Delimiter {
+    /// Byte range of the first match in `haystack`, if any.
+    fn find(&self, haystack: &str) -> Option<Range<usize>>;
+}
+
+/**
+ * A delimiter that can also be searched for from the back.
+ *
+ * Implementors promise that matches are never empty and never overlap, so
+ * searching from either end finds the same matches and `StrTok` can hand out
+ * tokens from both ends. A `str` delimiter does not qualify: `"aa"` matches
+ * `"aaa"` at 0 going forward but at 1 going backward.
+ */
+pub trait DoubleEndedDelimiter: Delimiter {
+    /// Byte range of the last match in `haystack`, if any.
+    fn rfind(&self, haystack: &str) -> Option<Range<usize>>;
+}
+
+/// A (possibly multi-char) string. The empty string matches at every `char`
+/// boundary, exactly like `str::split("")`.
+impl Delimiter for str {
+    fn find(&self, haystack: &str) -> Option<Range<usize>> {
+        if let &[byte] = self.as_bytes() {
+            return find_byte(byte, haystack);
+        }
+        haystack.find(self).map(|start| start..start + self.len())
+    }
+}
+
+impl Delimiter for String {
+    fn find(&self, haystack: &str) -> Option<Range<usize>> {
+        Delimiter::find(self.as_str(), haystack)
+    }
+}
+
+/// Fast path for ASCII delimiters: an ASCII byte never occurs inside a
+/// multi-byte `char`, so a plain byte search lands on a `char` boundary.
+fn find_byte(byte: u8, haystack: &str) -> Option<Range<usize>> {
+    let start = memchr(byte, haystack.as_bytes())?;
+    Some(start..start + 1)
+}
+
+/// A single `char`.
+impl Delimiter for char {
+    fn find(&self, haystack: &str) -> Option<Range<usize>> {
+        if let Ok(byte) = u8::try_from(*self) {
+            if byte.is_ascii() {
+                return find_byte(byte, haystack);
+            }
+        }
+        haystack
+            .find(*self)
+            .map(|start| start..start + self.len_utf8())
+    }
+}
+
+impl DoubleEndedDelimiter for char {
+    fn rfind(&self, haystack: &str) -> Option<Range<usize>> {
+        if let Ok(byte) = u8::try_from(*self) {
+            if byte.is_ascii() {
+                let start = memrchr(byte, haystack.as_bytes())?;
+                return Some(start..start + 1);
+            }
+        }
+        haystack
+            .rfind(*self)
+            .map(|start| start..start + self.len_utf8())
+    }
+}
+
+/// Any of a set of `char`s.
+impl Delimiter for [char] {
+    fn find(&self, haystack: &str) -> Option<Range<usize>> {
+        Delimiter::find(&|c: char| self.contains(&c), haystack)
+    }
+}
+
+impl DoubleEndedDelimiter for [char] {
+    fn rfind(&self, haystack: &str) -> Option<Range<usize>> {
+        DoubleEndedDelimiter::rfind(&|c: char| self.contains(&c), haystack)
+    }
+}
+
+impl<const N: usize> Delimiter for [char; N] {
+    fn find(&self, haystack: &str) -> Option<Range<usize>> {
+        Delimiter::find(&self[..], haystack)
+    }
+}
+
+impl<const N: usize> DoubleEndedDelimiter for [char; N] {
+    fn rfind(&self, haystack: &str) -> Option<Range<usize>> {
+        DoubleEndedDelimiter::rfind(&self[..], haystack)
+    }
+}
+
+/// Any `char` the predicate accepts.
+impl<F> Delimiter for F
+where
+    F: Fn(char) -> bool,
+{
+    fn find(&self, haystack: &str) -> Option<Range<usize>> {
+        haystack
+            .char_indices()
+            .find(|&(_, c)| self(c))
+            .map(|(start, c)| start..start + c.len_utf8())
+    }
+}
+
+impl<F> DoubleEndedDelimiter for F
+where
+    F: Fn(char) -> bool,
+{
+    fn rfind(&self, haystack: &str) -> Option<Range<usize>> {
+        haystack
+            .char_indices()
+            .rfind(|&(_, c)| self(c))
+            .map(|(start, c)| start..start + c.len_utf8())
+    }
+}
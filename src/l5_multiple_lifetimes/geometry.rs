@@ -0,0 +1,874 @@
+//! Shapes for layout calculations.
+//!
+//! Everything is in a y-up or y-down plane alike: nothing here depends on the
+//! orientation of the axes, or on the winding order of polygon vertices.
+
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+use std::ops::Range;
+
+mod index;
+
+pub use index::GridIndex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub w: f64,
+    pub h: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Center and radius.
+    Circle(Point, f64),
+    /// The corner with the smallest coordinates, and the size.
+    Rectangle(Point, Size),
+    Triangle(Point, Point, Point),
+    /// A simple (not self-intersecting) polygon, closed implicitly from the
+    /// last vertex back to the first.
+    Polygon(Vec<Point>),
+    /// Center and the radii along x and y.
+    Ellipse(Point, f64, f64),
+}
+use Shape::*;
+
+/// An axis-aligned box, edges included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// The box around no points at all; it contains and intersects nothing.
+    pub const EMPTY: BoundingBox = BoundingBox {
+        min: Point {
+            x: f64::INFINITY,
+            y: f64::INFINITY,
+        },
+        max: Point {
+            x: f64::NEG_INFINITY,
+            y: f64::NEG_INFINITY,
+        },
+    };
+
+    pub fn of_points(points: &[Point]) -> BoundingBox {
+        points
+            .iter()
+            .fold(BoundingBox::EMPTY, |bbox, p| BoundingBox {
+                min: Point {
+                    x: bbox.min.x.min(p.x),
+                    y: bbox.min.y.min(p.y),
+                },
+                max: Point {
+                    x: bbox.max.x.max(p.x),
+                    y: bbox.max.y.max(p.y),
+                },
+            })
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// The distance from `p` to the box; 0 if `p` is inside it.
+    pub fn distance(&self, p: Point) -> f64 {
+        let dx = (self.min.x - p.x).max(p.x - self.max.x).max(0.);
+        let dy = (self.min.y - p.y).max(p.y - self.max.y).max(0.);
+        dx.hypot(dy)
+    }
+}
+
+/**
+ * What the layout code needs to know about a shape.
+ *
+ * `Shape` covers the common cases, but nothing here matches on it: a type
+ * implementing `Geometry` works with [`compute_area`], [`select`] and the
+ * other functions of this module just like a `Shape` does, without changes to
+ * the module.
+ */
+pub trait Geometry {
+    fn area(&self) -> f64;
+
+    fn perimeter(&self) -> f64;
+
+    fn bounding_box(&self) -> BoundingBox;
+
+    /// The center of mass of the shape's area.
+    fn centroid(&self) -> Point;
+
+    /// Whether `p` lies inside the shape or on its boundary.
+    fn contains(&self, p: Point) -> bool;
+
+    /// The distance from `p` to the closest point of the shape; 0 if the shape
+    /// contains `p`.
+    ///
+    /// Defaults to the distance to the bounding box, which is exact for a box
+    /// and never more than the true distance otherwise. `Shape` and `ShapeRef`
+    /// override it with the exact distance.
+    fn distance(&self, p: Point) -> f64 {
+        self.bounding_box().distance(p)
+    }
+}
+
+/**
+ * A shape that borrows its vertices instead of owning them.
+ *
+ * A `Polygon` view is a slice of some vertex buffer, typically the one shared
+ * by a whole [`Scene`], so thousands of shapes can be measured without a
+ * `Vec<Point>` each. Views are `Copy`; [`Shape::view`] gives one for any
+ * owned shape.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeRef<'buf> {
+    Circle(Point, f64),
+    Rectangle(Point, Size),
+    Triangle(Point, Point, Point),
+    Polygon(&'buf [Point]),
+    Ellipse(Point, f64, f64),
+}
+
+impl Shape {
+    /// Borrows this shape as a [`ShapeRef`].
+    pub fn view(&self) -> ShapeRef<'_> {
+        match *self {
+            Circle(center, radius) => ShapeRef::Circle(center, radius),
+            Rectangle(corner, size) => ShapeRef::Rectangle(corner, size),
+            Triangle(a, b, c) => ShapeRef::Triangle(a, b, c),
+            Polygon(ref points) => ShapeRef::Polygon(points),
+            Ellipse(center, rx, ry) => ShapeRef::Ellipse(center, rx, ry),
+        }
+    }
+}
+
+impl ShapeRef<'_> {
+    /// Copies the view into an owned [`Shape`].
+    pub fn to_shape(self) -> Shape {
+        match self {
+            ShapeRef::Circle(center, radius) => Circle(center, radius),
+            ShapeRef::Rectangle(corner, size) => Rectangle(corner, size),
+            ShapeRef::Triangle(a, b, c) => Triangle(a, b, c),
+            ShapeRef::Polygon(points) => Polygon(points.to_vec()),
+            ShapeRef::Ellipse(center, rx, ry) => Ellipse(center, rx, ry),
+        }
+    }
+}
+
+impl Geometry for ShapeRef<'_> {
+    fn area(&self) -> f64 {
+        match *self {
+            ShapeRef::Circle(_, radius) => PI * radius * radius,
+            ShapeRef::Rectangle(_, size) => size.w * size.h,
+            ShapeRef::Triangle(a, b, c) => polygon_area(&[a, b, c]),
+            ShapeRef::Polygon(points) => polygon_area(points),
+            ShapeRef::Ellipse(_, rx, ry) => PI * rx * ry,
+        }
+    }
+
+    fn perimeter(&self) -> f64 {
+        match *self {
+            ShapeRef::Circle(_, radius) => 2. * PI * radius,
+            ShapeRef::Rectangle(_, size) => 2. * (size.w + size.h),
+            ShapeRef::Triangle(a, b, c) => polygon_perimeter(&[a, b, c]),
+            ShapeRef::Polygon(points) => polygon_perimeter(points),
+            ShapeRef::Ellipse(_, rx, ry) => ellipse_perimeter(rx, ry),
+        }
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        match *self {
+            ShapeRef::Circle(center, radius) => ellipse_bounding_box(center, radius, radius),
+            ShapeRef::Rectangle(corner, size) => BoundingBox {
+                min: corner,
+                max: Point {
+                    x: corner.x + size.w,
+                    y: corner.y + size.h,
+                },
+            },
+            ShapeRef::Triangle(a, b, c) => BoundingBox::of_points(&[a, b, c]),
+            ShapeRef::Polygon(points) => BoundingBox::of_points(points),
+            ShapeRef::Ellipse(center, rx, ry) => ellipse_bounding_box(center, rx, ry),
+        }
+    }
+
+    fn centroid(&self) -> Point {
+        match *self {
+            ShapeRef::Circle(center, _) | ShapeRef::Ellipse(center, _, _) => center,
+            ShapeRef::Rectangle(corner, size) => Point {
+                x: corner.x + size.w / 2.,
+                y: corner.y + size.h / 2.,
+            },
+            ShapeRef::Triangle(a, b, c) => polygon_centroid(&[a, b, c]),
+            ShapeRef::Polygon(points) => polygon_centroid(points),
+        }
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        match *self {
+            ShapeRef::Circle(center, radius) => ellipse_contains(center, radius, radius, p),
+            ShapeRef::Rectangle(..) => self.bounding_box().contains(p),
+            ShapeRef::Triangle(a, b, c) => polygon_contains(&[a, b, c], p),
+            ShapeRef::Polygon(points) => polygon_contains(points, p),
+            ShapeRef::Ellipse(center, rx, ry) => ellipse_contains(center, rx, ry, p),
+        }
+    }
+
+    fn distance(&self, p: Point) -> f64 {
+        match *self {
+            ShapeRef::Circle(center, radius) => {
+                ((p.x - center.x).hypot(p.y - center.y) - radius).max(0.)
+            }
+            ShapeRef::Rectangle(..) => self.bounding_box().distance(p),
+            ShapeRef::Triangle(a, b, c) => polygon_distance(&[a, b, c], p),
+            ShapeRef::Polygon(points) => polygon_distance(points, p),
+            ShapeRef::Ellipse(center, rx, ry) => ellipse_distance(center, rx, ry, p),
+        }
+    }
+}
+
+impl Geometry for Shape {
+    fn area(&self) -> f64 {
+        self.view().area()
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.view().perimeter()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.view().bounding_box()
+    }
+
+    fn centroid(&self) -> Point {
+        self.view().centroid()
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        self.view().contains(p)
+    }
+
+    fn distance(&self, p: Point) -> f64 {
+        self.view().distance(p)
+    }
+}
+
+/**
+ * Shapes over one shared vertex buffer.
+ *
+ * Polygons and triangles are given as indices into the buffer, and the
+ * scene keeps one `ShapeRef<'buf>` per shape. A polygon borrows its slice of
+ * the buffer, so its vertices are never copied; a triangle copies its three
+ * vertices, which is no bigger than indices would be. Either way the shapes
+ * can be handed out for as long as the buffer lives, independently of the
+ * scene.
+ */
+#[derive(Debug, Clone)]
+pub struct Scene<'buf> {
+    vertices: &'buf [Point],
+    shapes: Vec<ShapeRef<'buf>>,
+}
+
+impl<'buf> Scene<'buf> {
+    pub fn new(vertices: &'buf [Point]) -> Self {
+        Scene {
+            vertices,
+            shapes: Vec::new(),
+        }
+    }
+
+    /// Adds the polygon made of `vertices[range]`.
+    pub fn polygon(&mut self, range: Range<usize>) -> &mut Self {
+        self.push(ShapeRef::Polygon(&self.vertices[range]))
+    }
+
+    /// Adds the triangle made of the vertices at `indices`, copying them out of
+    /// the buffer.
+    pub fn triangle(&mut self, indices: [usize; 3]) -> &mut Self {
+        let [a, b, c] = indices.map(|i| self.vertices[i]);
+        self.push(ShapeRef::Triangle(a, b, c))
+    }
+
+    pub fn push(&mut self, shape: ShapeRef<'buf>) -> &mut Self {
+        self.shapes.push(shape);
+        self
+    }
+
+    pub fn shapes(&self) -> &[ShapeRef<'buf>] {
+        &self.shapes
+    }
+
+    pub fn total_area(&self) -> f64 {
+        self.shapes.iter().map(compute_area).sum()
+    }
+}
+
+pub fn compute_area<G: Geometry + ?Sized>(shape: &G) -> f64 {
+    shape.area()
+}
+
+pub fn perimeter<G: Geometry + ?Sized>(shape: &G) -> f64 {
+    shape.perimeter()
+}
+
+pub fn bounding_box<G: Geometry + ?Sized>(shape: &G) -> BoundingBox {
+    shape.bounding_box()
+}
+
+pub fn centroid<G: Geometry + ?Sized>(shape: &G) -> Point {
+    shape.centroid()
+}
+
+pub fn contains<G: Geometry + ?Sized>(shape: &G, p: Point) -> bool {
+    shape.contains(p)
+}
+
+pub fn distance<G: Geometry + ?Sized>(shape: &G, p: Point) -> f64 {
+    shape.distance(p)
+}
+
+/// `r1` if the shape's area is above `threshold`, `r2` otherwise.
+///
+/// The shape is only measured, never returned, so its borrow gets a lifetime
+/// of its own: the result lives as long as the candidates, however short-lived
+/// the shape is.
+pub fn select<'a, T: ?Sized>(shape: &dyn Geometry, threshold: f64, r1: &'a T, r2: &'a T) -> &'a T {
+    if compute_area(shape) > threshold {
+        r1
+    } else {
+        r2
+    }
+}
+
+/// The candidate with the highest threshold that the shape's area is above
+/// (the first one, on a tie), or `None` if the area is above none of them.
+///
+/// `select(shape, t, r1, r2)` is `select_many(shape, &[(t, r1), (f64::NEG_INFINITY, r2)])`.
+pub fn select_many<'a, T: ?Sized>(
+    shape: &dyn Geometry,
+    candidates: &[(f64, &'a T)],
+) -> Option<&'a T> {
+    let area = compute_area(shape);
+    candidates
+        .iter()
+        .filter(|&&(threshold, _)| area > threshold)
+        .fold(None, |best: Option<&(f64, &'a T)>, candidate| match best {
+            Some(best) if best.0 >= candidate.0 => Some(best),
+            _ => Some(candidate),
+        })
+        .map(|&(_, chosen)| chosen)
+}
+
+/// The polygon's edges as `(from, to)` pairs, including the closing one.
+fn edges(points: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    let closing = points.last().copied();
+    closing
+        .into_iter()
+        .chain(points.iter().copied())
+        .zip(points.iter().copied())
+}
+
+/// Twice the signed area (shoelace formula); positive when counterclockwise
+/// in a y-up plane.
+fn twice_signed_area(points: &[Point]) -> f64 {
+    edges(points).map(|(a, b)| a.x * b.y - b.x * a.y).sum()
+}
+
+pub fn polygon_area(points: &[Point]) -> f64 {
+    twice_signed_area(points).abs() / 2.
+}
+
+pub fn polygon_perimeter(points: &[Point]) -> f64 {
+    edges(points)
+        .map(|(a, b)| (b.x - a.x).hypot(b.y - a.y))
+        .sum()
+}
+
+/// The centroid of the polygon's area. A degenerate polygon (all vertices on
+/// a line) has no area; the mean of its vertices is used instead, which is
+/// NaN for a polygon without vertices.
+pub fn polygon_centroid(points: &[Point]) -> Point {
+    let twice_area = twice_signed_area(points);
+    if twice_area == 0. {
+        let n = points.len() as f64;
+        return Point {
+            x: points.iter().map(|p| p.x).sum::<f64>() / n,
+            y: points.iter().map(|p| p.y).sum::<f64>() / n,
+        };
+    }
+    let (x, y) = edges(points).fold((0., 0.), |(x, y), (a, b)| {
+        let cross = a.x * b.y - b.x * a.y;
+        (x + (a.x + b.x) * cross, y + (a.y + b.y) * cross)
+    });
+    Point {
+        x: x / (3. * twice_area),
+        y: y / (3. * twice_area),
+    }
+}
+
+/// Even-odd (ray casting) test, with points on an edge counted as inside.
+pub fn polygon_contains(points: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(points) {
+        if on_segment(a, b, p) {
+            return true;
+        }
+        if (a.y > p.y) != (b.y > p.y) {
+            let crossing = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if p.x < crossing {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// The distance from `p` to the polygon: 0 inside, otherwise to the closest
+/// edge. A polygon without vertices is infinitely far from everything.
+pub fn polygon_distance(points: &[Point], p: Point) -> f64 {
+    if polygon_contains(points, p) {
+        return 0.;
+    }
+    edges(points)
+        .map(|(a, b)| segment_distance(a, b, p))
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn segment_distance(a: Point, b: Point, p: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0. {
+        0.
+    } else {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_squared).clamp(0., 1.)
+    };
+    (a.x + t * dx - p.x).hypot(a.y + t * dy - p.y)
+}
+
+fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    let cross = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    cross.abs() <= f64::EPSILON * (b.x - a.x).abs().max((b.y - a.y).abs()).max(1.)
+        && BoundingBox::of_points(&[a, b]).contains(p)
+}
+
+/// Ramanujan's second approximation; exact for circles, and within a few
+/// parts per million for ellipses up to 10:1.
+fn ellipse_perimeter(rx: f64, ry: f64) -> f64 {
+    let h = ((rx - ry) / (rx + ry)).powi(2);
+    PI * (rx + ry) * (1. + 3. * h / (10. + (4. - 3. * h).sqrt()))
+}
+
+fn ellipse_bounding_box(center: Point, rx: f64, ry: f64) -> BoundingBox {
+    BoundingBox {
+        min: Point {
+            x: center.x - rx,
+            y: center.y - ry,
+        },
+        max: Point {
+            x: center.x + rx,
+            y: center.y + ry,
+        },
+    }
+}
+
+fn ellipse_contains(center: Point, rx: f64, ry: f64, p: Point) -> bool {
+    let dx = (p.x - center.x) / rx;
+    let dy = (p.y - center.y) / ry;
+    dx * dx + dy * dy <= 1.
+}
+
+/// The distance from `p` to the ellipse. The closest point on the outline is
+/// found by iterating on its direction from the center (by symmetry, in the
+/// quadrant of `p`), converging to well below a millionth of the radii within
+/// a few steps. A flat ellipse is a segment.
+fn ellipse_distance(center: Point, rx: f64, ry: f64, p: Point) -> f64 {
+    if rx == 0. || ry == 0. {
+        let a = Point {
+            x: center.x - rx,
+            y: center.y - ry,
+        };
+        let b = Point {
+            x: center.x + rx,
+            y: center.y + ry,
+        };
+        return segment_distance(a, b, p);
+    }
+    if ellipse_contains(center, rx, ry, p) {
+        return 0.;
+    }
+    let (px, py) = ((p.x - center.x).abs(), (p.y - center.y).abs());
+    let (mut tx, mut ty) = (FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+    for _ in 0..4 {
+        // Approximate the outline near the current point by its circle of
+        // curvature, centered at (ex, ey), and move to where the ray from that
+        // center towards `p` crosses the circle.
+        let ex = (rx * rx - ry * ry) * tx.powi(3) / rx;
+        let ey = (ry * ry - rx * rx) * ty.powi(3) / ry;
+        let r = (rx * tx - ex).hypot(ry * ty - ey);
+        let (qx, qy) = (px - ex, py - ey);
+        let q = qx.hypot(qy);
+        tx = ((qx * r / q + ex) / rx).clamp(0., 1.);
+        ty = ((qy * r / q + ey) / ry).clamp(0., 1.);
+        let t = tx.hypot(ty);
+        tx /= t;
+        ty /= t;
+    }
+    (rx * tx - px).hypot(ry * ty - py)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9 * expected.abs().max(1.),
+            "{actual} != {expected}"
+        );
+    }
+
+    fn square() -> Vec<Point> {
+        vec![pt(0., 0.), pt(2., 0.), pt(2., 2.), pt(0., 2.)]
+    }
+
+    #[test]
+    fn areas() {
+        assert_close(compute_area(&Circle(pt(5., 5.), 1.)), PI);
+        assert_close(
+            compute_area(&Rectangle(pt(0., 0.), Size { w: 2., h: 3. })),
+            6.,
+        );
+        assert_close(
+            compute_area(&Triangle(pt(0., 0.), pt(4., 0.), pt(0., 3.))),
+            6.,
+        );
+        assert_close(compute_area(&Polygon(square())), 4.);
+        assert_close(compute_area(&Ellipse(pt(0., 0.), 2., 1.)), 2. * PI);
+    }
+
+    #[test]
+    fn winding_order_does_not_matter() {
+        let mut clockwise = square();
+        clockwise.reverse();
+
+        assert_close(compute_area(&Polygon(clockwise.clone())), 4.);
+        assert_eq!(centroid(&Polygon(clockwise)), pt(1., 1.));
+    }
+
+    #[test]
+    fn perimeters() {
+        assert_close(perimeter(&Circle(pt(0., 0.), 1.)), 2. * PI);
+        assert_close(perimeter(&Ellipse(pt(0., 0.), 1., 1.)), 2. * PI);
+        assert_close(
+            perimeter(&Rectangle(pt(0., 0.), Size { w: 2., h: 3. })),
+            10.,
+        );
+        assert_close(
+            perimeter(&Triangle(pt(0., 0.), pt(4., 0.), pt(0., 3.))),
+            12.,
+        );
+        assert_close(perimeter(&Polygon(square())), 8.);
+        // The exact perimeter of a 2:1 ellipse with radii 2 and 1.
+        assert!((perimeter(&Ellipse(pt(0., 0.), 2., 1.)) - 9.688448220).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bounding_boxes() {
+        let expected = BoundingBox {
+            min: pt(-1., 0.),
+            max: pt(3., 2.),
+        };
+
+        assert_eq!(bounding_box(&Ellipse(pt(1., 1.), 2., 1.)), expected);
+        assert_eq!(
+            bounding_box(&Triangle(pt(-1., 2.), pt(3., 0.), pt(0., 1.))),
+            expected
+        );
+        assert_eq!(
+            bounding_box(&Rectangle(pt(-1., 0.), Size { w: 4., h: 2. })),
+            expected
+        );
+        assert_eq!(bounding_box(&Polygon(vec![])), BoundingBox::EMPTY);
+        assert!(!BoundingBox::EMPTY.contains(pt(0., 0.)));
+    }
+
+    #[test]
+    fn centroids() {
+        assert_eq!(centroid(&Circle(pt(1., 2.), 3.)), pt(1., 2.));
+        assert_eq!(
+            centroid(&Rectangle(pt(0., 0.), Size { w: 2., h: 4. })),
+            pt(1., 2.)
+        );
+        let c = centroid(&Triangle(pt(0., 0.), pt(3., 0.), pt(0., 3.)));
+        assert_close(c.x, 1.);
+        assert_close(c.y, 1.);
+
+        // An L shape: two unit-wide arms of a 2x2 square.
+        let l = Polygon(vec![
+            pt(0., 0.),
+            pt(2., 0.),
+            pt(2., 1.),
+            pt(1., 1.),
+            pt(1., 2.),
+            pt(0., 2.),
+        ]);
+        let c = centroid(&l);
+        assert_close(c.x, 5. / 6.);
+        assert_close(c.y, 5. / 6.);
+
+        let flat = Polygon(vec![pt(0., 0.), pt(1., 0.), pt(2., 0.)]);
+        assert_eq!(centroid(&flat), pt(1., 0.));
+    }
+
+    #[test]
+    fn distances() {
+        let p = pt(3.9, 3.9);
+        // Inside the triangle's bounding box, but 1.9√2 from its hypotenuse.
+        assert_close(
+            distance(&Triangle(pt(0., 0.), pt(4., 0.), pt(0., 4.)), p),
+            1.9 * 2f64.sqrt(),
+        );
+        assert_close(distance(&Circle(pt(0., 0.), 1.), pt(3., 4.)), 4.);
+        assert_eq!(distance(&Circle(pt(0., 0.), 1.), pt(0.5, 0.)), 0.);
+        assert_close(
+            distance(&Rectangle(pt(0., 0.), Size { w: 2., h: 3. }), pt(5., 7.)),
+            5.,
+        );
+        assert_eq!(distance(&Polygon(square()), pt(1., 1.)), 0.);
+        assert_close(distance(&Polygon(square()), pt(1., -3.)), 3.);
+        assert_eq!(distance(&Polygon(vec![]), pt(0., 0.)), f64::INFINITY);
+        assert_close(distance(&Ellipse(pt(1., 1.), 2., 1.), pt(1., 4.)), 2.);
+        assert_close(distance(&Ellipse(pt(1., 1.), 2., 1.), pt(-4., 1.)), 3.);
+        assert_eq!(distance(&Ellipse(pt(0., 0.), 2., 0.), pt(1., 1.)), 1.);
+    }
+
+    #[test]
+    fn ellipse_distance_agrees_with_sampling() {
+        for (rx, ry) in [(2., 1.), (1., 3.), (5., 0.5), (1., 1.)] {
+            let ellipse = Ellipse(pt(1., -1.), rx, ry);
+            for i in 0..24 {
+                let angle = i as f64 * PI / 12. + 0.1;
+                let p = pt(1. + 7. * angle.cos(), -1. + 4. * angle.sin());
+                let sampled = (0..100_000)
+                    .map(|j| {
+                        let t = j as f64 * 2. * PI / 100_000.;
+                        (1. + rx * t.cos() - p.x).hypot(-1. + ry * t.sin() - p.y)
+                    })
+                    .fold(f64::INFINITY, f64::min);
+                let d = distance(&ellipse, p);
+                assert!((d - sampled).abs() < 1e-6, "{p:?}: {d} vs {sampled}");
+            }
+        }
+    }
+
+    /// A shape the module knows nothing about.
+    struct Square {
+        corner: Point,
+        side: f64,
+    }
+
+    impl Geometry for Square {
+        fn area(&self) -> f64 {
+            self.side * self.side
+        }
+
+        fn perimeter(&self) -> f64 {
+            4. * self.side
+        }
+
+        fn bounding_box(&self) -> BoundingBox {
+            BoundingBox {
+                min: self.corner,
+                max: pt(self.corner.x + self.side, self.corner.y + self.side),
+            }
+        }
+
+        fn centroid(&self) -> Point {
+            pt(
+                self.corner.x + self.side / 2.,
+                self.corner.y + self.side / 2.,
+            )
+        }
+
+        fn contains(&self, p: Point) -> bool {
+            self.bounding_box().contains(p)
+        }
+    }
+
+    #[test]
+    fn custom_shapes_plug_in() {
+        let square = Square {
+            corner: pt(0., 0.),
+            side: 3.,
+        };
+        assert_eq!(compute_area(&square), 9.);
+        assert_eq!(centroid(&square), pt(1.5, 1.5));
+        assert_eq!(distance(&square, pt(6., 7.)), 5.);
+
+        let shapes: Vec<Box<dyn Geometry>> =
+            vec![Box::new(square), Box::new(Circle(pt(0., 0.), 1.))];
+        let total: f64 = shapes.iter().map(|shape| compute_area(&**shape)).sum();
+        assert_close(total, 9. + PI);
+
+        let (big, small) = ("big", "small");
+        assert_eq!(select(&*shapes[0], 5., &big, &small), &"big");
+        assert_eq!(select(&*shapes[1], 5., &big, &small), &"small");
+    }
+
+    #[test]
+    fn views_measure_like_their_shapes() {
+        let shapes = [
+            Circle(pt(1., 2.), 3.),
+            Rectangle(pt(0., 0.), Size { w: 2., h: 3. }),
+            Triangle(pt(0., 0.), pt(4., 0.), pt(0., 3.)),
+            Polygon(square()),
+            Ellipse(pt(0., 0.), 2., 1.),
+        ];
+
+        for shape in &shapes {
+            let view = shape.view();
+            assert_eq!(compute_area(&view), compute_area(shape));
+            assert_eq!(perimeter(&view), perimeter(shape));
+            assert_eq!(bounding_box(&view), bounding_box(shape));
+            assert_eq!(view.to_shape(), *shape);
+        }
+    }
+
+    #[test]
+    fn scene_shares_one_vertex_buffer() {
+        let vertices = vec![
+            pt(0., 0.),
+            pt(2., 0.),
+            pt(2., 2.),
+            pt(0., 2.),
+            pt(10., 10.),
+            pt(13., 10.),
+        ];
+        let mut scene = Scene::new(&vertices);
+        scene
+            .polygon(0..4)
+            .triangle([4, 5, 0])
+            .push(ShapeRef::Circle(pt(0., 0.), 1.));
+
+        let shapes = scene.shapes();
+        assert_eq!(shapes.len(), 3);
+        assert_close(compute_area(&shapes[0]), 4.);
+        assert_close(compute_area(&shapes[1]), 15.);
+        assert_close(scene.total_area(), 4. + 15. + PI);
+        assert!(matches!(shapes[0], ShapeRef::Polygon(p) if p.as_ptr() == vertices.as_ptr()));
+
+        let (big, small) = ("big", "small");
+        assert_eq!(select(&shapes[1], 10., &big, &small), &"big");
+        assert_eq!(select(&shapes[0], 10., &big, &small), &"small");
+    }
+
+    #[test]
+    fn views_outlive_the_scene() {
+        let vertices = square();
+
+        let view = {
+            let mut scene = Scene::new(&vertices);
+            scene.polygon(1..4);
+            scene.shapes()[0]
+        }; // the scene is gone, the view still borrows `vertices`
+
+        assert_close(compute_area(&view), 2.);
+    }
+
+    #[test]
+    fn select_outlives_a_temporary_shape() {
+        let (big, small) = (String::from("big"), String::from("small"));
+
+        let chosen: &str = select(&Circle(pt(0., 0.), 1.), 3., &big, &small);
+
+        assert_eq!(chosen, "big");
+    }
+
+    #[test]
+    fn select_many_by_thresholds() {
+        let labels = ["huge", "small", "medium", "tiny"];
+        let candidates = [
+            (100., labels[0]),
+            (1., labels[1]),
+            (10., labels[2]),
+            (0., labels[3]),
+        ];
+        let pick = |side: f64| {
+            let square = Rectangle(pt(0., 0.), Size { w: side, h: side });
+            select_many(&square, &candidates)
+        };
+
+        assert_eq!(pick(20.), Some("huge"));
+        assert_eq!(pick(5.), Some("medium"));
+        assert_eq!(pick(2.), Some("small"));
+        assert_eq!(pick(1.), Some("tiny"));
+        assert_eq!(pick(0.), None);
+        assert_eq!(
+            select_many(&Circle(pt(0., 0.), 1.), &[] as &[(f64, &str)]),
+            None
+        );
+    }
+
+    #[test]
+    fn select_many_generalizes_select() {
+        let (r1, r2) = (&1, &2);
+        for radius in [0.5, 1., 2.] {
+            let circle = Circle(pt(0., 0.), radius);
+            for threshold in [0., 3., PI, 10.] {
+                assert_eq!(
+                    select_many(&circle, &[(threshold, r1), (f64::NEG_INFINITY, r2)]),
+                    Some(select(&circle, threshold, r1, r2))
+                );
+            }
+        }
+        assert_eq!(
+            select_many(&Circle(pt(0., 0.), 1.), &[(0., "a"), (0., "b")]),
+            Some("a")
+        );
+    }
+
+    #[test]
+    fn containment() {
+        let triangle = Triangle(pt(0., 0.), pt(4., 0.), pt(0., 4.));
+        assert!(contains(&triangle, pt(1., 1.)));
+        assert!(contains(&triangle, pt(2., 2.))); // on the hypotenuse
+        assert!(contains(&triangle, pt(0., 0.)));
+        assert!(!contains(&triangle, pt(3., 3.)));
+
+        let l = Polygon(vec![
+            pt(0., 0.),
+            pt(2., 0.),
+            pt(2., 1.),
+            pt(1., 1.),
+            pt(1., 2.),
+            pt(0., 2.),
+        ]);
+        assert!(contains(&l, pt(0.5, 1.5)));
+        assert!(!contains(&l, pt(1.5, 1.5)));
+
+        assert!(contains(&Circle(pt(0., 0.), 1.), pt(0., 1.)));
+        assert!(!contains(&Circle(pt(0., 0.), 1.), pt(0.8, 0.8)));
+        assert!(contains(&Ellipse(pt(0., 0.), 2., 1.), pt(1.9, 0.)));
+        assert!(!contains(&Ellipse(pt(0., 0.), 2., 1.), pt(0., 1.1)));
+        assert!(contains(
+            &Rectangle(pt(0., 0.), Size { w: 1., h: 1. }),
+            pt(1., 0.5)
+        ));
+        assert!(!contains(&Polygon(vec![]), pt(0., 0.)));
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+
+use super::{BoundingBox, Geometry, Point, Shape};
+
+type Cell = (i64, i64);
+
+/// The most cells one shape is filed under. Bigger shapes, and shapes without
+/// a finite bounding box, are kept aside and checked by every query instead.
+const MAX_CELLS_PER_SHAPE: f64 = 256.;
+
+/**
+ * A uniform-grid spatial index over borrowed shapes.
+ *
+ * The plane is divided into square cells, and each shape is filed under every
+ * cell its bounding box touches, so a query only looks at the shapes near it.
+ * Pick a cell size around the size of a typical shape: much smaller and big
+ * shapes are filed under many cells, much larger and each cell holds many
+ * shapes. A shape that would cover more than a few hundred cells is not
+ * filed under any; every query checks it on its own, which keeps inserting it
+ * cheap and also handles shapes with an infinite bounding box.
+ *
+ * The index stores `&'a G`s borrowed from the caller's collection, so it
+ * cannot outlive that collection, while the shapes it returns are `&'a G` as
+ * well and can outlive the index.
+ */
+pub struct GridIndex<'a, G: ?Sized = Shape> {
+    cell_size: f64,
+    shapes: Vec<(&'a G, BoundingBox)>,
+    cells: HashMap<Cell, Vec<usize>>,
+    /// The shapes too big for the grid, see [`MAX_CELLS_PER_SHAPE`].
+    oversized: Vec<usize>,
+    /// The smallest and largest occupied cell coordinates: queries never look
+    /// at cells outside of them.
+    extent: Option<(Cell, Cell)>,
+}
+
+impl<'a, G: Geometry + ?Sized> GridIndex<'a, G> {
+    pub fn new(cell_size: f64) -> Self {
+        assert!(
+            cell_size > 0. && cell_size.is_finite(),
+            "cell size must be positive"
+        );
+        GridIndex {
+            cell_size,
+            shapes: Vec::new(),
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+            extent: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    fn cell(&self, p: Point) -> Cell {
+        (
+            (p.x / self.cell_size).floor() as i64,
+            (p.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    pub fn insert(&mut self, shape: &'a G) {
+        let bbox = shape.bounding_box();
+        let id = self.shapes.len();
+        self.shapes.push((shape, bbox));
+        if bbox == BoundingBox::EMPTY {
+            return;
+        }
+        // Counted in floating point, where an unbounded box is simply too big.
+        let span =
+            |lo: f64, hi: f64| (hi / self.cell_size).floor() - (lo / self.cell_size).floor() + 1.;
+        let cells = span(bbox.min.x, bbox.max.x) * span(bbox.min.y, bbox.max.y);
+        if cells.is_nan() || cells > MAX_CELLS_PER_SHAPE {
+            self.oversized.push(id);
+            return;
+        }
+
+        let (min, max) = (self.cell(bbox.min), self.cell(bbox.max));
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(id);
+            }
+        }
+        self.extent = Some(match self.extent {
+            None => (min, max),
+            Some((lo, hi)) => (
+                (lo.0.min(min.0), lo.1.min(min.1)),
+                (hi.0.max(max.0), hi.1.max(max.1)),
+            ),
+        });
+    }
+
+    /// The shapes containing `p`, in insertion order.
+    pub fn query_point(&self, p: Point) -> Vec<&'a G> {
+        let filed = self.cells.get(&self.cell(p)).into_iter().flatten();
+        let mut ids: Vec<usize> = filed.chain(&self.oversized).copied().collect();
+        ids.sort_unstable();
+        ids.into_iter()
+            .map(|id| self.shapes[id])
+            .filter(|(shape, bbox)| bbox.contains(p) && shape.contains(p))
+            .map(|(shape, _)| shape)
+            .collect()
+    }
+
+    /// The shapes whose bounding boxes intersect `area`, in insertion order.
+    ///
+    /// Only the cells of `area` that hold shapes at all are looked at, and
+    /// when those are more than the shapes themselves, the shapes are checked
+    /// one by one instead.
+    pub fn query_box(&self, area: &BoundingBox) -> Vec<&'a G> {
+        if area == &BoundingBox::EMPTY {
+            return Vec::new();
+        }
+        let mut ids = self.oversized.clone();
+        if let Some((lo, hi)) = self.extent {
+            let (min, max) = (self.cell(area.min), self.cell(area.max));
+            let (min, max) = (
+                (min.0.max(lo.0), min.1.max(lo.1)),
+                (max.0.min(hi.0), max.1.min(hi.1)),
+            );
+            if min.0 <= max.0 && min.1 <= max.1 {
+                let cells = (max.0 - min.0 + 1).saturating_mul(max.1 - min.1 + 1);
+                if cells as u64 > self.shapes.len() as u64 {
+                    ids.extend(0..self.shapes.len());
+                } else {
+                    ids.extend(
+                        (min.0..=max.0)
+                            .flat_map(|x| (min.1..=max.1).map(move |y| (x, y)))
+                            .filter_map(|cell| self.cells.get(&cell))
+                            .flatten(),
+                    );
+                }
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter()
+            .map(|id| self.shapes[id])
+            .filter(|(_, bbox)| bbox.intersects(area))
+            .map(|(shape, _)| shape)
+            .collect()
+    }
+
+    /// The shape closest to `p` by [`Geometry::distance`] (0 if it contains
+    /// `p`); the first inserted one, on a tie. Shapes with an empty bounding
+    /// box are never found.
+    ///
+    /// Cells are searched in rings around `p`, starting from the first ring
+    /// that reaches a shape, and only within the occupied part of the grid.
+    /// Bounding boxes, which are never farther than their shapes, rule out
+    /// shapes and rings that cannot beat the best shape so far; once the rings
+    /// have covered as many cells as there are shapes, the shapes are checked
+    /// one by one instead.
+    pub fn nearest(&self, p: Point) -> Option<&'a G> {
+        let mut best = None;
+        for &id in &self.oversized {
+            best = self.closer(p, id, best);
+        }
+        let Some((lo, hi)) = self.extent else {
+            return best.map(|(_, id)| self.shapes[id].0);
+        };
+        let center = self.cell(p);
+        // Chebyshev distances from `center` to the closest and the farthest
+        // occupied cell.
+        let first = [
+            lo.0.saturating_sub(center.0),
+            center.0.saturating_sub(hi.0),
+            lo.1.saturating_sub(center.1),
+            center.1.saturating_sub(hi.1),
+        ]
+        .into_iter()
+        .fold(0, i64::max);
+        let last = [
+            hi.0.saturating_sub(center.0),
+            center.0.saturating_sub(lo.0),
+            hi.1.saturating_sub(center.1),
+            center.1.saturating_sub(lo.1),
+        ]
+        .into_iter()
+        .fold(0, i64::max);
+
+        let mut budget = self.shapes.len();
+        for ring in first..=last {
+            let cells = ring_cells(center, ring, (lo, hi));
+            budget = budget.saturating_sub(cells.len());
+            if budget == 0 {
+                for id in 0..self.shapes.len() {
+                    best = self.closer(p, id, best);
+                }
+                break;
+            }
+            for cell in cells {
+                for &id in self.cells.get(&cell).into_iter().flatten() {
+                    best = self.closer(p, id, best);
+                }
+            }
+            // Every cell past this ring is at least `ring` cells away from `p`.
+            if best.is_some_and(|(d, _)| d <= ring as f64 * self.cell_size) {
+                break;
+            }
+        }
+        best.map(|(_, id)| self.shapes[id].0)
+    }
+
+    /// `best`, or shape `id` with its distance if it is closer to `p`.
+    fn closer(&self, p: Point, id: usize, best: Option<(f64, usize)>) -> Option<(f64, usize)> {
+        let (shape, bbox) = self.shapes[id];
+        let beats = |distance: f64| best.is_none_or(|(d, b)| (distance, id) < (d, b));
+        if bbox == BoundingBox::EMPTY || !beats(bbox.distance(p)) {
+            return best;
+        }
+        let distance = shape.distance(p);
+        if beats(distance) {
+            Some((distance, id))
+        } else {
+            best
+        }
+    }
+}
+
+/// The cells at Chebyshev distance exactly `ring` from `center` that lie
+/// within `extent`.
+fn ring_cells(center: Cell, ring: i64, extent: (Cell, Cell)) -> Vec<Cell> {
+    let ((x0, y0), (x1, y1)) = extent;
+    let (cx, cy) = center;
+    let xs = cx.saturating_sub(ring).max(x0)..=cx.saturating_add(ring).min(x1);
+    let ys = cy.saturating_sub(ring - 1).max(y0)..=cy.saturating_add(ring - 1).min(y1);
+    let mut cells = Vec::new();
+    for y in [cy.saturating_sub(ring), cy.saturating_add(ring)] {
+        if (y0..=y1).contains(&y) {
+            cells.extend(xs.clone().map(|x| (x, y)));
+        }
+        if ring == 0 {
+            break;
+        }
+    }
+    for x in [cx.saturating_sub(ring), cx.saturating_add(ring)] {
+        if ring > 0 && (x0..=x1).contains(&x) {
+            cells.extend(ys.clone().map(|y| (x, y)));
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+    use crate::l5_multiple_lifetimes::geometry::{ShapeRef, Size};
+    use Shape::*;
+
+    fn pt(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    fn shapes() -> Vec<Shape> {
+        vec![
+            Circle(pt(0., 0.), 1.),
+            Rectangle(pt(5., 5.), Size { w: 10., h: 2. }),
+            Triangle(pt(0., 0.), pt(4., 0.), pt(0., 4.)),
+            Polygon(vec![]),
+            Ellipse(pt(-20., 3.), 2., 1.),
+        ]
+    }
+
+    fn index(shapes: &[Shape]) -> GridIndex<'_> {
+        let mut index = GridIndex::new(2.);
+        for shape in shapes {
+            index.insert(shape);
+        }
+        index
+    }
+
+    #[test]
+    fn point_queries() {
+        let shapes = shapes();
+        let index = index(&shapes);
+
+        assert_eq!(index.len(), 5);
+        assert_eq!(index.query_point(pt(0.5, 0.5)), [&shapes[0], &shapes[2]]);
+        assert_eq!(index.query_point(pt(3., 0.5)), [&shapes[2]]);
+        assert_eq!(index.query_point(pt(14., 6.)), [&shapes[1]]);
+        assert_eq!(index.query_point(pt(-18., 3.)), [&shapes[4]]);
+        assert!(index.query_point(pt(3., 3.)).is_empty());
+        assert!(index.query_point(pt(100., 100.)).is_empty());
+    }
+
+    #[test]
+    fn box_queries() {
+        let shapes = shapes();
+        let index = index(&shapes);
+        let area = |x0, y0, x1, y1| BoundingBox {
+            min: pt(x0, y0),
+            max: pt(x1, y1),
+        };
+
+        assert_eq!(
+            index.query_box(&area(-1., -1., 6., 6.)),
+            [&shapes[0], &shapes[1], &shapes[2]]
+        );
+        assert_eq!(index.query_box(&area(-30., 0., -19., 2.5)), [&shapes[4]]);
+        assert!(index.query_box(&area(20., 20., 30., 30.)).is_empty());
+        assert!(index.query_box(&BoundingBox::EMPTY).is_empty());
+    }
+
+    #[test]
+    fn nearest_neighbor() {
+        let shapes = shapes();
+        let index = index(&shapes);
+
+        assert_eq!(index.nearest(pt(0.1, 0.1)), Some(&shapes[0]));
+        assert_eq!(index.nearest(pt(9., 9.)), Some(&shapes[1]));
+        assert_eq!(index.nearest(pt(-10., 3.)), Some(&shapes[4]));
+        assert_eq!(index.nearest(pt(1000., -1000.)), Some(&shapes[1]));
+        assert_eq!(GridIndex::<Shape>::new(1.).nearest(pt(0., 0.)), None);
+    }
+
+    #[test]
+    fn nearest_measures_the_shape_not_its_box() {
+        let shapes = [
+            Triangle(pt(0., 0.), pt(4., 0.), pt(0., 4.)),
+            Circle(pt(5.5, 5.5), 1.),
+        ];
+        let mut index = GridIndex::new(2.);
+        index.insert(&shapes[0]);
+        index.insert(&shapes[1]);
+
+        // Inside the triangle's bounding box, but closer to the circle.
+        assert_eq!(index.nearest(pt(3.9, 3.9)), Some(&shapes[1]));
+        assert_eq!(index.nearest(pt(1.9, 1.9)), Some(&shapes[0]));
+    }
+
+    #[test]
+    fn nearest_agrees_with_brute_force() {
+        let shapes: Vec<Shape> = (0..60)
+            .map(|i| {
+                let c = pt((i * 37 % 101) as f64 - 50., (i * 53 % 97) as f64 - 48.);
+                let r = (i % 4) as f64 + 0.5;
+                match i % 3 {
+                    0 => Circle(c, r),
+                    1 => Triangle(c, pt(c.x + 3. * r, c.y), pt(c.x, c.y + 3. * r)),
+                    _ => Ellipse(c, 2. * r, r),
+                }
+            })
+            .collect();
+        let index = index(&shapes);
+
+        for i in 0..200 {
+            let scale = if i % 10 == 0 { 100. } else { 1. };
+            let p = pt(
+                ((i * 17 % 131) as f64 - 65.) * scale,
+                ((i * 29 % 127) as f64 - 63.) * scale,
+            );
+            let expected = shapes
+                .iter()
+                .min_by(|a, b| a.distance(p).total_cmp(&b.distance(p)))
+                .unwrap();
+
+            assert!(ptr::eq(index.nearest(p).unwrap(), expected), "{p:?}");
+        }
+    }
+
+    #[test]
+    fn far_away_queries() {
+        let circle = Circle(pt(0., 0.), 1.);
+        let mut index = GridIndex::new(1.);
+        index.insert(&circle);
+
+        // However far the query is from the shapes, only occupied cells are
+        // visited, so these return at once.
+        assert_eq!(index.nearest(pt(3000., 0.)), Some(&circle));
+        assert_eq!(index.nearest(pt(-1e15, 1e15)), Some(&circle));
+        let everywhere = BoundingBox {
+            min: pt(-1e15, -1e15),
+            max: pt(1e15, 1e15),
+        };
+        assert_eq!(index.query_box(&everywhere), [&circle]);
+
+        // Two shapes far apart, the query halfway between them.
+        let far = Circle(pt(1e6, 1e6), 1.);
+        index.insert(&far);
+        assert_eq!(index.nearest(pt(6e5, 6e5)), Some(&far));
+        assert_eq!(index.nearest(pt(4e5, 4e5)), Some(&circle));
+        assert_eq!(index.query_box(&everywhere), [&circle, &far]);
+    }
+
+    #[test]
+    fn oversized_shapes() {
+        let shapes = [
+            Circle(pt(0., 0.), 3000.),
+            Rectangle(
+                pt(10., -5.),
+                Size {
+                    w: f64::INFINITY,
+                    h: 1.,
+                },
+            ),
+            Circle(pt(5000., 0.), 1.),
+        ];
+        let mut index = GridIndex::new(1.);
+        for shape in &shapes {
+            index.insert(shape);
+        }
+
+        // Neither big shape is filed under cells; only the small circle is.
+        assert!(index.cells.values().all(|ids| ids == &[2]));
+        assert_eq!(index.oversized, [0, 1]);
+
+        assert_eq!(index.query_point(pt(100., 100.)), [&shapes[0]]);
+        assert_eq!(index.query_point(pt(1e300, -4.5)), [&shapes[1]]);
+        assert_eq!(index.query_point(pt(2000., -4.5)), [&shapes[0], &shapes[1]]);
+        let area = BoundingBox {
+            min: pt(4000., -10.),
+            max: pt(6000., -4.),
+        };
+        assert_eq!(index.query_box(&area), [&shapes[1]]);
+        let area = BoundingBox {
+            min: pt(4000., -10.),
+            max: pt(6000., 10.),
+        };
+        assert_eq!(index.query_box(&area), [&shapes[1], &shapes[2]]);
+
+        assert_eq!(index.nearest(pt(0., 0.)), Some(&shapes[0]));
+        assert_eq!(index.nearest(pt(1e9, -4.)), Some(&shapes[1]));
+        assert_eq!(index.nearest(pt(5000., -3.5)), Some(&shapes[1]));
+        assert_eq!(index.nearest(pt(5000., 1.5)), Some(&shapes[2]));
+
+        let mut only_big = GridIndex::new(1.);
+        only_big.insert(&shapes[1]);
+        assert_eq!(only_big.nearest(pt(0., 0.)), Some(&shapes[1]));
+        assert_eq!(only_big.query_box(&area), [&shapes[1]]);
+    }
+
+    #[test]
+    fn results_outlive_the_index() {
+        let shapes = shapes();
+
+        let found = {
+            let index = index(&shapes);
+            index.query_point(pt(14., 6.))
+        }; // the index is gone, the shapes it found are still borrowed from `shapes`
+
+        assert_eq!(found, [&shapes[1]]);
+    }
+
+    #[test]
+    fn any_geometry() {
+        let vertices = [pt(0., 0.), pt(2., 0.), pt(2., 2.), pt(0., 2.)];
+        let view = ShapeRef::Polygon(&vertices);
+        let circle = Circle(pt(10., 10.), 1.);
+        let mut index: GridIndex<'_, dyn Geometry> = GridIndex::new(4.);
+        index.insert(&view);
+        index.insert(&circle);
+
+        assert_eq!(index.query_point(pt(1., 1.)).len(), 1);
+        assert_eq!(index.nearest(pt(8., 8.)).unwrap().centroid(), pt(10., 10.));
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn index_cannot_outlive_its_shapes() {
+        let index = {
+            let shapes = shapes();
+            let mut index = GridIndex::new(1.);
+            for shape in &shapes {
+                index.insert(shape); // `shapes` does not live long enough
+            }
+            index
+        };
+        println!("{}", index.len());
+    }
+}
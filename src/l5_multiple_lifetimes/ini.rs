@@ -0,0 +1,269 @@
+//! `key = value` configuration with `[section]` headers.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use super::{until_char, StrTok};
+
+/**
+ * A parsed configuration, borrowing its keys and values from the source text.
+ *
+ * They are `Cow`s only so that [`Config::to_owned`] can detach a config from
+ * its source: a freshly parsed `Config<'src>` holds nothing but borrowed
+ * slices.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config<'src> {
+    sections: Vec<Section<'src>>,
+}
+
+/// The entries under one `[name]` header. Entries before the first header
+/// belong to a section named `""`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section<'src> {
+    pub name: Cow<'src, str>,
+    pub entries: Vec<(Cow<'src, str>, Cow<'src, str>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based.
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// `[section` without the closing `]`.
+    UnclosedSection,
+    /// A line that is neither a header, a comment, nor `key = value`.
+    MissingEquals,
+    EmptyKey,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            ErrorKind::UnclosedSection => "expected `]`",
+            ErrorKind::MissingEquals => "expected `=`",
+            ErrorKind::EmptyKey => "missing key before `=`",
+        };
+        write!(f, "line {}: {problem}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<'src> Config<'src> {
+    /// Parses `src`. Blank lines and lines starting with `;` or `#` are
+    /// skipped; keys, values and section names are trimmed.
+    pub fn parse(src: &'src str) -> Result<Self, ParseError> {
+        let mut sections = vec![Section {
+            name: Cow::Borrowed(""),
+            entries: Vec::new(),
+        }];
+        for (index, line) in StrTok::new(src, &'\n').enumerate() {
+            let error = |kind| ParseError {
+                line: index + 1,
+                kind,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with([';', '#']) {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or(error(ErrorKind::UnclosedSection))?;
+                sections.push(Section {
+                    name: Cow::Borrowed(name.trim()),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+
+            let key = until_char(line, '=');
+            if key.len() == line.len() {
+                return Err(error(ErrorKind::MissingEquals));
+            }
+            let value = &line[key.len() + 1..];
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(error(ErrorKind::EmptyKey));
+            }
+            let section = sections.last_mut().expect("there is always a section");
+            section
+                .entries
+                .push((Cow::Borrowed(key), Cow::Borrowed(value.trim())));
+        }
+        Ok(Config { sections })
+    }
+
+    /// The value of `key` in `section` (`""` for entries before any header).
+    /// When a key is repeated, the last one wins.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .rev()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.entries.iter().rev())
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| &**value)
+    }
+
+    /// Every section named `name`, in the order they appear. A name may
+    /// repeat; [`Config::get`] looks through all of them, the last one first.
+    pub fn sections_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Section<'src>> + 'a {
+        self.sections.iter().filter(move |s| s.name == name)
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &Section<'src>> {
+        self.sections
+            .iter()
+            .filter(|s| !(s.name.is_empty() && s.entries.is_empty()))
+    }
+
+    /// Copies every key and value out of the source, so the config can
+    /// outlive it.
+    pub fn to_owned(&self) -> Config<'static> {
+        let owned = |s: &Cow<'_, str>| Cow::Owned(s.clone().into_owned());
+        Config {
+            sections: self
+                .sections
+                .iter()
+                .map(|section| Section {
+                    name: owned(&section.name),
+                    entries: section
+                        .entries
+                        .iter()
+                        .map(|(key, value)| (owned(key), owned(value)))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "\
+; global settings
+name = lessons
+debug=true
+
+[server]
+host = 127.0.0.1
+port = 8080
+url = http://localhost:8080/?a=b
+
+# later keys win
+[server]
+port = 9090
+";
+
+    #[test]
+    fn keys_and_values() {
+        let config = Config::parse(SRC).unwrap();
+
+        assert_eq!(config.get("", "name"), Some("lessons"));
+        assert_eq!(config.get("", "debug"), Some("true"));
+        assert_eq!(config.get("server", "host"), Some("127.0.0.1"));
+        assert_eq!(config.get("server", "port"), Some("9090"));
+        assert_eq!(
+            config.get("server", "url"),
+            Some("http://localhost:8080/?a=b")
+        );
+        assert_eq!(config.get("server", "name"), None);
+        assert_eq!(config.get("client", "host"), None);
+    }
+
+    #[test]
+    fn sections() {
+        let config = Config::parse(SRC).unwrap();
+
+        let names: Vec<&str> = config.sections().map(|s| &*s.name).collect();
+        assert_eq!(names, ["", "server", "server"]);
+        let sizes: Vec<usize> = config
+            .sections_named("server")
+            .map(|s| s.entries.len())
+            .collect();
+        assert_eq!(sizes, [3, 1]);
+        assert_eq!(config.sections_named("client").count(), 0);
+
+        let config = Config::parse("[only]\nkey = value").unwrap();
+        let names: Vec<&str> = config.sections().map(|s| &*s.name).collect();
+        assert_eq!(names, ["only"]);
+    }
+
+    #[test]
+    fn repeated_sections_agree_with_get() {
+        let config = Config::parse("[a]\nk=1\n[b]\nk=2\n[a]\nk=3").unwrap();
+
+        assert_eq!(config.get("a", "k"), Some("3"));
+        let values: Vec<&str> = config
+            .sections_named("a")
+            .flat_map(|s| &s.entries)
+            .map(|(_, value)| &**value)
+            .collect();
+        assert_eq!(values, ["1", "3"]);
+    }
+
+    #[test]
+    fn everything_is_borrowed() {
+        let src = String::from("[s]\nkey = value\r\n");
+        let config = Config::parse(&src).unwrap();
+        let section = config.sections_named("s").next().unwrap();
+
+        assert!(matches!(section.name, Cow::Borrowed("s")));
+        let (key, value) = &section.entries[0];
+        assert!(matches!(key, Cow::Borrowed(_)));
+        assert_eq!(value.as_ptr(), src[10..].as_ptr());
+        assert_eq!(value, "value");
+    }
+
+    #[test]
+    fn line_numbered_errors() {
+        let error = Config::parse("a = 1\n\n[broken\nb = 2").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                line: 3,
+                kind: ErrorKind::UnclosedSection
+            }
+        );
+        assert_eq!(error.to_string(), "line 3: expected `]`");
+
+        let error = Config::parse("[s]\njust words").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected `=`");
+
+        let error = Config::parse(" = value").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::EmptyKey);
+    }
+
+    #[test]
+    fn to_owned_outlives_the_source() {
+        let config: Config<'static> = {
+            let src = String::from("[s]\nkey = value");
+            Config::parse(&src).unwrap().to_owned()
+        }; // `src` is gone
+
+        assert_eq!(config.get("s", "key"), Some("value"));
+        assert_eq!(config, Config::parse("[s]\nkey = value").unwrap());
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn borrowed_config_does_not_outlive_the_source() {
+        let config = {
+            let src = String::from("key = value");
+            Config::parse(&src).unwrap() // `src` does not live long enough
+        };
+        println!("{config:?}");
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/**
+ * Deduplicates tokens into small, `Copy` [`Symbol`]s.
+ *
+ * Two lifetimes, two jobs:
+ * - `'a` is how long the interned strings live. [`Interner::intern`] stores
+ *   the `&'a str` it is given, so tokens from a `StrTok` cost nothing to
+ *   intern; [`Interner::intern_leaked`] copies a string into a leaked,
+ *   `'static` allocation instead, for text that would not otherwise live long
+ *   enough. An `Interner<'id, 'static>` only ever hands out `&'static str`s.
+ * - `'id` is a brand. Every interner gets a fresh one from [`Interner::scope`],
+ *   and a `Symbol<'id>` only resolves against the interner with the same
+ *   `'id`, so mixing up symbols from two interners is a compile error rather
+ *   than a wrong string or an out-of-bounds panic.
+ *
+ * The brand is invariant (see `Brand`) so the compiler can neither shrink
+ * nor grow it to make two interners' brands agree, and the closure passed to
+ * `scope` is generic over `'id`, so no symbol can escape it.
+ */
+pub struct Interner<'id, 'a> {
+    strings: Vec<&'a str>,
+    symbols: HashMap<&'a str, Symbol<'id>>,
+}
+
+/// `fn(&'id ()) -> &'id ()` is both contravariant and covariant in `'id`,
+/// which makes it invariant.
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+/// A handle to a string in the [`Interner`] branded `'id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol<'id> {
+    index: u32,
+    brand: Brand<'id>,
+}
+
+impl<'a> Interner<'_, 'a> {
+    /// Runs `f` with a new interner, branded with a lifetime no other
+    /// interner has.
+    pub fn scope<R>(f: impl for<'id> FnOnce(Interner<'id, 'a>) -> R) -> R {
+        f(Interner {
+            strings: Vec::new(),
+            symbols: HashMap::new(),
+        })
+    }
+}
+
+impl<'id, 'a> Interner<'id, 'a> {
+    /// Interns `token` without copying it.
+    pub fn intern(&mut self, token: &'a str) -> Symbol<'id> {
+        if let Some(&symbol) = self.symbols.get(token) {
+            return symbol;
+        }
+        let symbol = Symbol {
+            index: u32::try_from(self.strings.len()).expect("more than u32::MAX symbols"),
+            brand: PhantomData,
+        };
+        self.strings.push(token);
+        self.symbols.insert(token, symbol);
+        symbol
+    }
+
+    /// Interns a copy of `token` that lives for the rest of the program. Only
+    /// new strings are copied (and leaked), so interning the same text again
+    /// costs nothing.
+    pub fn intern_leaked(&mut self, token: &str) -> Symbol<'id> {
+        match self.symbols.get(token) {
+            Some(&symbol) => symbol,
+            None => self.intern(Box::leak(token.into())),
+        }
+    }
+
+    pub fn intern_all(&mut self, tokens: impl IntoIterator<Item = &'a str>) -> Vec<Symbol<'id>> {
+        tokens.into_iter().map(|token| self.intern(token)).collect()
+    }
+
+    /// The string behind `symbol`. It borrows the original text, not the
+    /// interner, so it can outlive the interner.
+    pub fn resolve(&self, symbol: Symbol<'id>) -> &'a str {
+        // The brand guarantees `symbol` came from this interner.
+        self.strings[symbol.index as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l5_multiple_lifetimes::StrTok;
+
+    #[test]
+    fn deduplicates_tokens() {
+        let haystack = String::from("to be or not to be");
+
+        Interner::scope(|mut interner| {
+            let symbols = interner.intern_all(StrTok::new(&haystack, &' '));
+
+            assert_eq!(interner.len(), 4);
+            assert_eq!(symbols[0], symbols[4]);
+            assert_eq!(symbols[1], symbols[5]);
+            assert_ne!(symbols[0], symbols[1]);
+            assert_eq!(interner.resolve(symbols[3]), "not");
+            // Borrowed, not copied: the first "to" is the one stored.
+            assert_eq!(interner.resolve(symbols[4]).as_ptr(), haystack.as_ptr());
+        });
+    }
+
+    #[test]
+    fn strings_outlive_the_interner() {
+        let haystack = String::from("b a b");
+
+        let unique: Vec<&str> = Interner::scope(|mut interner| {
+            interner.intern_all(StrTok::new(&haystack, &' '));
+            interner.strings.clone()
+        });
+
+        assert_eq!(unique, ["b", "a"]);
+    }
+
+    #[test]
+    fn leaked_interner_is_static() {
+        let resolved: &'static str = Interner::scope(|mut interner: Interner<'_, 'static>| {
+            let from_temporary = interner.intern_leaked(&String::from("seoul"));
+            let again = interner.intern_leaked("seoul");
+            let literal = interner.intern("busan");
+
+            assert_eq!(from_temporary, again);
+            assert_eq!(interner.len(), 2);
+            assert_eq!(interner.resolve(literal), "busan");
+            interner.resolve(from_temporary)
+        });
+
+        assert_eq!(resolved, "seoul");
+    }
+
+    #[test]
+    fn borrowed_and_leaked_strings_mix() {
+        let haystack = String::from("a b");
+
+        Interner::scope(|mut interner| {
+            let a = interner.intern_all(StrTok::new(&haystack, &' '))[0];
+
+            assert_eq!(interner.intern_leaked("a"), a);
+            let c = interner.intern_leaked(&"c".repeat(2));
+            assert_eq!(interner.resolve(c), "cc");
+        });
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn symbols_do_not_cross_interners() {
+        Interner::scope(|mut first| {
+            let symbol = first.intern("a");
+            Interner::scope(|second: Interner<'_, 'static>| {
+                second.resolve(symbol); // borrowed data escapes outside of closure: the brands differ
+            });
+        });
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn symbols_do_not_escape_the_scope() {
+        let symbol = Interner::scope(|mut interner| interner.intern("a")); // lifetime may not live long enough
+    }
+}
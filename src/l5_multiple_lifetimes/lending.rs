@@ -0,0 +1,290 @@
+use super::{Delimiter, StrTok};
+
+/**
+ * A lending (streaming) iterator: its items borrow from the iterator itself,
+ * so only one can be alive at a time.
+ *
+ * That is what lets a tokenizer reuse one scratch buffer for every token it
+ * has to rewrite, instead of allocating a `String` per token like `Iterator`
+ * would force it to.
+ *
+ * The item type lives in a separate [`Lend`] family rather than as
+ * `type Item<'a> where Self: 'a` on this trait: with the `where Self: 'a`
+ * bound, any adapter taking a closure over items (`for<'a> FnMut(Self::Item<'a>)`)
+ * would require `Self: 'static`, due to a current limitation of the type
+ * system, and a tokenizer borrowing a non-`'static` haystack could never be
+ * mapped or filtered.
+ *
+ * Items are produced in two steps, [`advance`] and [`get`], so that adapters
+ * like [`filter`] can look at an item and move on without holding it across
+ * the next `advance`.
+ *
+ * [`advance`]: LendingIterator::advance
+ * [`get`]: LendingIterator::get
+ * [`filter`]: LendingIterator::filter
+ */
+pub trait LendingIterator {
+    type Lend: Lend;
+
+    /// Moves on to the next item.
+    fn advance(&mut self);
+
+    /// The current item, or `None` once the iterator is exhausted (or before
+    /// the first `advance`).
+    fn get(&self) -> Option<Lent<'_, Self>>;
+
+    fn next(&mut self) -> Option<Lent<'_, Self>> {
+        self.advance();
+        self.get()
+    }
+
+    /// Turns each lent item into an owned value, giving a regular `Iterator`.
+    fn map<B, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Lent<'_, Self>) -> B,
+    {
+        Map { iter: self, f }
+    }
+
+    fn filter<P>(self, predicate: P) -> Filter<Self, P>
+    where
+        Self: Sized,
+        P: FnMut(&Lent<'_, Self>) -> bool,
+    {
+        Filter {
+            iter: self,
+            predicate,
+        }
+    }
+
+    fn for_each<F>(mut self, mut f: F)
+    where
+        Self: Sized,
+        F: FnMut(Lent<'_, Self>),
+    {
+        while let Some(item) = self.next() {
+            f(item);
+        }
+    }
+}
+
+/// A family of item types, one per lifetime.
+pub trait Lend {
+    type Item<'a>;
+}
+
+/// The item a [`LendingIterator`] lends for `'a`.
+pub type Lent<'a, I> = <<I as LendingIterator>::Lend as Lend>::Item<'a>;
+
+/// Lends `&'a str`.
+pub enum LendStr {}
+
+impl Lend for LendStr {
+    type Item<'a> = &'a str;
+}
+
+pub struct Map<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<B, I, F> Iterator for Map<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(Lent<'_, I>) -> B,
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        Some((self.f)(item))
+    }
+}
+
+pub struct Filter<I, P> {
+    iter: I,
+    predicate: P,
+}
+
+impl<I, P> LendingIterator for Filter<I, P>
+where
+    I: LendingIterator,
+    P: FnMut(&Lent<'_, I>) -> bool,
+{
+    type Lend = I::Lend;
+
+    fn advance(&mut self) {
+        loop {
+            self.iter.advance();
+            match self.iter.get() {
+                Some(item) if !(self.predicate)(&item) => continue,
+                _ => break,
+            }
+        }
+    }
+
+    fn get(&self) -> Option<Lent<'_, Self>> {
+        self.iter.get()
+    }
+}
+
+/**
+ * Tokens with backslash escapes resolved (`\n`, `\t`, `\r`, `\0`, and `\x` for
+ * any other `x`).
+ *
+ * Tokens without a backslash are lent straight from the haystack; the others
+ * are rewritten into one scratch buffer that is reused for every token.
+ */
+pub struct Unescaped<'haystack, 'delim, D: ?Sized = str> {
+    tokens: StrTok<'haystack, 'delim, D>,
+    scratch: String,
+    current: Current<'haystack>,
+}
+
+enum Current<'haystack> {
+    Done,
+    Haystack(&'haystack str),
+    Scratch,
+}
+
+impl<'haystack, 'delim, D: Delimiter + ?Sized> StrTok<'haystack, 'delim, D> {
+    /// Resolves backslash escapes in each token.
+    pub fn unescaped(self) -> Unescaped<'haystack, 'delim, D> {
+        Unescaped {
+            tokens: self,
+            scratch: String::new(),
+            current: Current::Done,
+        }
+    }
+}
+
+impl<D: Delimiter + ?Sized> LendingIterator for Unescaped<'_, '_, D> {
+    type Lend = LendStr;
+
+    fn advance(&mut self) {
+        self.current = match self.tokens.next() {
+            None => Current::Done,
+            Some(token) if !token.contains('\\') => Current::Haystack(token),
+            Some(token) => {
+                self.scratch.clear();
+                unescape(token, &mut self.scratch);
+                Current::Scratch
+            }
+        };
+    }
+
+    fn get(&self) -> Option<&str> {
+        match self.current {
+            Current::Done => None,
+            Current::Haystack(token) => Some(token),
+            Current::Scratch => Some(&self.scratch),
+        }
+    }
+}
+
+fn unescape(token: &str, out: &mut String) {
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('0') => out.push('\0'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescaped_tokens() {
+        let mut tokens = StrTok::new(r"plain tab\there back\\slash trailing\", &' ').unescaped();
+
+        assert_eq!(tokens.next(), Some("plain"));
+        assert_eq!(tokens.next(), Some("tab\there"));
+        assert_eq!(tokens.next(), Some(r"back\slash"));
+        assert_eq!(tokens.next(), Some(r"trailing\"));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.get(), None);
+    }
+
+    #[test]
+    fn scratch_buffer_is_reused() {
+        let mut tokens = StrTok::new(r"a\tlonger\tone b\tc", &' ').unescaped();
+
+        let first = tokens.next().unwrap().as_ptr();
+        let second = tokens.next().unwrap().as_ptr();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn untouched_tokens_borrow_the_haystack() {
+        let haystack = String::from(r"a\tb c");
+        let mut tokens = StrTok::new(&haystack, &' ').unescaped();
+
+        tokens.next();
+        let token = tokens.next().unwrap();
+
+        assert_eq!(token, "c");
+        assert_eq!(token.as_ptr(), haystack[5..].as_ptr());
+    }
+
+    #[test]
+    fn map_to_owned_values() {
+        let haystack = String::from(r"1\0 22 3\t3");
+        let lengths: Vec<usize> = StrTok::new(&haystack, &' ')
+            .unescaped()
+            .map(str::len)
+            .collect();
+
+        assert_eq!(lengths, [2, 2, 3]);
+    }
+
+    #[test]
+    fn filter_and_for_each() {
+        let haystack = String::from(r"keep\tme drop keep\ttoo");
+        let mut kept = Vec::new();
+
+        StrTok::new(&haystack, &' ')
+            .unescaped()
+            .filter(|token| token.starts_with("keep"))
+            .for_each(|token| kept.push(token.to_owned()));
+
+        assert_eq!(kept, ["keep\tme", "keep\ttoo"]);
+
+        let mut tokens = StrTok::new(&haystack, "  ")
+            .unescaped()
+            .filter(|token| token.len() > 100);
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn cannot_hold_two_items_at_once() {
+        let mut tokens = StrTok::new(r"a\tb c\td", &' ').unescaped();
+
+        let first = tokens.next();
+        let second = tokens.next(); // cannot borrow `tokens` as mutable more than once at a time
+        println!("{first:?} {second:?}");
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn cannot_keep_an_item_past_the_iterator() {
+        let token = {
+            let mut tokens = StrTok::new(r"a\tb", &' ').unescaped();
+            tokens.next() // `tokens` does not live long enough
+        };
+        println!("{token:?}");
+    }
+}
@@ -0,0 +1,133 @@
+//! Word-at-a-time byte search, the fast path for single-byte delimiters.
+//!
+//! Instead of comparing one byte at a time, eight (on 64-bit targets) bytes are
+//! XORed with the needle repeated across a word; a matching byte becomes zero,
+//! and a zero byte can be detected in the whole word with a couple of
+//! arithmetic operations.
+
+const WORD: usize = std::mem::size_of::<usize>();
+const LO: usize = usize::MAX / 0xff;
+const HI: usize = LO << 7;
+
+/// Whether any byte of `word` is zero. The answer is exact, it just doesn't say
+/// which byte, so the caller finishes with a byte-wise scan.
+fn has_zero_byte(word: usize) -> bool {
+    word.wrapping_sub(LO) & !word & HI != 0
+}
+
+fn read_word(chunk: &[u8]) -> usize {
+    usize::from_ne_bytes(chunk.try_into().expect("chunk of one word"))
+}
+
+/// Offset of the first `needle` in `haystack`.
+pub(super) fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let repeated = LO * needle as usize;
+    let mut offset = 0;
+    for chunk in haystack.chunks_exact(WORD) {
+        if has_zero_byte(read_word(chunk) ^ repeated) {
+            break;
+        }
+        offset += WORD;
+    }
+    haystack[offset..]
+        .iter()
+        .position(|&b| b == needle)
+        .map(|i| offset + i)
+}
+
+/// Offset of the last `needle` in `haystack`.
+pub(super) fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let repeated = LO * needle as usize;
+    let mut end = haystack.len();
+    for chunk in haystack.rchunks_exact(WORD) {
+        if has_zero_byte(read_word(chunk) ^ repeated) {
+            break;
+        }
+        end -= WORD;
+    }
+    haystack[..end].iter().rposition(|&b| b == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l5_multiple_lifetimes::StrTok;
+    use std::hint::black_box;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn agrees_with_naive_search() {
+        let haystack: Vec<u8> = (0..100u32).map(|i| (i * 37 % 251) as u8).collect();
+        for needle in [0, 1, b' ', 0x7f, 0x80, 0xfe, 0xff] {
+            for start in 0..WORD + 1 {
+                for end in start..haystack.len() {
+                    let slice = &haystack[start..end];
+                    assert_eq!(
+                        memchr(needle, slice),
+                        slice.iter().position(|&b| b == needle),
+                        "{needle} in {start}..{end}"
+                    );
+                    assert_eq!(
+                        memrchr(needle, slice),
+                        slice.iter().rposition(|&b| b == needle),
+                        "{needle} in {start}..{end}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finds_every_position() {
+        for len in 0..3 * WORD {
+            for at in 0..len {
+                let mut haystack = vec![b'a'; len];
+                haystack[at] = b' ';
+                assert_eq!(memchr(b' ', &haystack), Some(at));
+                assert_eq!(memrchr(b' ', &haystack), Some(at));
+            }
+            assert_eq!(memchr(b' ', &vec![b'a'; len]), None);
+        }
+    }
+
+    /// Runs `f` a few times to warm up, then reports the best and the median
+    /// of `SAMPLES` timed runs.
+    fn bench(name: &str, bytes: usize, mut f: impl FnMut() -> usize) {
+        const SAMPLES: usize = 15;
+
+        for _ in 0..3 {
+            black_box(f());
+        }
+        let mut samples: Vec<Duration> = (0..SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                black_box(f());
+                start.elapsed()
+            })
+            .collect();
+        samples.sort();
+
+        let median = samples[SAMPLES / 2];
+        let throughput = bytes as f64 / median.as_secs_f64() / (1 << 30) as f64;
+        println!(
+            "{name:<24} best {:>10.3?}  median {:>10.3?}  {throughput:>6.2} GiB/s",
+            samples[0], median
+        );
+    }
+
+    /// `cargo test --release bench_single_byte_delimiter -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_single_byte_delimiter() {
+        let line = "2024-01-02T10:11:12Z GET /static/app.js 200 1532 Mozilla/5.0\n";
+        let haystack = line.repeat((16 << 20) / line.len());
+
+        bench("StrTok (fast path)", haystack.len(), || {
+            StrTok::new(&haystack, &' ').count()
+        });
+        bench("StrTok (generic path)", haystack.len(), || {
+            StrTok::new(&haystack, &|c: char| c == ' ').count()
+        });
+        bench("str::split", haystack.len(), || haystack.split(' ').count());
+    }
+}
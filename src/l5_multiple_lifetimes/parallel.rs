@@ -0,0 +1,114 @@
+use std::panic;
+use std::thread;
+
+use super::{Delimiter, DoubleEndedDelimiter, StrTok};
+
+/**
+ * Tokenizes `haystack` on up to `threads` threads, returning the same tokens
+ * as `StrTok::new(haystack, delimiter)`, in order.
+ *
+ * The haystack is cut into chunks at delimiter matches, and each chunk is
+ * tokenized by its own `StrTok` on a scoped thread. Scoped threads are what
+ * make this work without copying: they are joined before `thread::scope`
+ * returns, so they may borrow `haystack` and `delimiter` even though neither
+ * is `'static`, and the tokens they send back are `&'haystack str`s.
+ *
+ * Cutting only at matches needs a [`DoubleEndedDelimiter`]: its matches are
+ * the same wherever the search starts, so a chunk boundary can never split or
+ * shift one.
+ */
+pub fn par_tokenize<'haystack, D>(
+    haystack: &'haystack str,
+    delimiter: &D,
+    threads: usize,
+) -> Vec<&'haystack str>
+where
+    D: DoubleEndedDelimiter + Sync + ?Sized,
+{
+    let chunks = chunks(haystack, delimiter, threads.max(1));
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || StrTok::new(chunk, delimiter).collect::<Vec<_>>()))
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    })
+}
+
+/// Up to `n` chunks of roughly equal length, each ending just before a
+/// delimiter match. The matches themselves are left out, so the tokens of the
+/// chunks, concatenated, are the tokens of the haystack.
+fn chunks<'haystack, D>(haystack: &'haystack str, delimiter: &D, n: usize) -> Vec<&'haystack str>
+where
+    D: DoubleEndedDelimiter + ?Sized,
+{
+    let mut chunks = Vec::with_capacity(n);
+    let mut start = 0;
+    for k in 1..n {
+        let mut at = (haystack.len() * k / n).max(start);
+        while !haystack.is_char_boundary(at) {
+            at += 1;
+        }
+        let Some(m) = Delimiter::find(delimiter, &haystack[at..]) else {
+            break;
+        };
+        chunks.push(&haystack[start..at + m.start]);
+        start = at + m.end;
+    }
+    chunks.push(&haystack[start..]);
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l5_multiple_lifetimes::tests::corpus;
+
+    #[test]
+    fn same_tokens_as_str_tok() {
+        for haystack in corpus(&["a", "bc", " ", ",", "→"]) {
+            for threads in 0..6 {
+                let expected: Vec<&str> = StrTok::new(&haystack, &' ').collect();
+                assert_eq!(
+                    par_tokenize(&haystack, &' ', threads),
+                    expected,
+                    "{haystack:?} on {threads} threads"
+                );
+
+                let expected: Vec<&str> = StrTok::new(&haystack, &[',', '→']).collect();
+                assert_eq!(
+                    par_tokenize(&haystack, &[',', '→'], threads),
+                    expected,
+                    "{haystack:?} on {threads} threads"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_end_before_delimiters() {
+        let haystack = "aaaa bbbb cccc dddd";
+
+        assert_eq!(chunks(haystack, &' ', 2), ["aaaa bbbb", "cccc dddd"]);
+        assert_eq!(chunks(haystack, &' ', 4), ["aaaa", "bbbb", "cccc", "dddd"]);
+        assert_eq!(chunks("no delimiter here", &',', 4), ["no delimiter here"]);
+    }
+
+    #[test]
+    fn tokens_borrow_a_local_haystack() {
+        let line = "2024-01-02 GET /index.html 200\n";
+        let haystack = line.repeat(1000);
+        let is_separator = |c: char| c == ' ' || c == '\n';
+
+        let tokens = par_tokenize(&haystack, &is_separator, 4);
+
+        assert_eq!(tokens.len(), 4 * 1000 + 1);
+        assert_eq!(tokens[4 * 999 + 2], "/index.html");
+        assert_eq!(tokens[0].as_ptr(), haystack.as_ptr());
+    }
+}
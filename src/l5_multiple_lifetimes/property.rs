@@ -0,0 +1,196 @@
+//! Property tests: `StrTok` against `str::split` on random inputs.
+//!
+//! Cases are drawn from a small alphabet (ASCII, multi-byte, a combining mark,
+//! and the characters delimiters are made of) so that delimiters actually
+//! match. A failing case is shrunk to a minimal one before it is reported,
+//! together with the seed that produced it.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::StrTok;
+
+const SEED: u64 = 0x5EED_70C5;
+const CASES: u64 = 5000;
+const ALPHABET: &[char] = &['a', 'b', ' ', ',', 'é', '서', '🦀', '\u{301}'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Case {
+    haystack: String,
+    delimiter: String,
+    limit: Option<usize>,
+}
+
+impl Case {
+    fn generate(rng: &mut StdRng) -> Case {
+        let mut text = |max: usize| -> String {
+            let len = rng.gen_range(0..=max);
+            (0..len)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())])
+                .collect()
+        };
+        let haystack = text(16);
+        let delimiter = text(3);
+        let limit = rng.gen_bool(0.3).then(|| rng.gen_range(0..5));
+        Case {
+            haystack,
+            delimiter,
+            limit,
+        }
+    }
+
+    /// Cases one step simpler than `self`: a character removed, a character
+    /// replaced by `a`, or a smaller (or no) limit.
+    fn shrink(&self) -> Vec<Case> {
+        let mut simpler = Vec::new();
+        for haystack in simplify(&self.haystack) {
+            simpler.push(Case {
+                haystack,
+                ..self.clone()
+            });
+        }
+        for delimiter in simplify(&self.delimiter) {
+            simpler.push(Case {
+                delimiter,
+                ..self.clone()
+            });
+        }
+        if let Some(n) = self.limit {
+            simpler.push(Case {
+                limit: n.checked_sub(1),
+                ..self.clone()
+            });
+        }
+        simpler
+    }
+}
+
+fn simplify(text: &str) -> Vec<String> {
+    let mut simpler = Vec::new();
+    for (i, c) in text.char_indices() {
+        let (before, after) = (&text[..i], &text[i + c.len_utf8()..]);
+        simpler.push(format!("{before}{after}"));
+        if c != 'a' {
+            simpler.push(format!("{before}a{after}"));
+        }
+    }
+    simpler
+}
+
+/// Shrinks a failing `case` greedily: as long as one of its simplifications
+/// still fails, move to that one.
+fn shrink<P>(mut case: Case, mut message: String, property: &P) -> (Case, String)
+where
+    P: Fn(&Case) -> Result<(), String>,
+{
+    'simpler: loop {
+        for candidate in case.shrink() {
+            if let Err(m) = property(&candidate) {
+                case = candidate;
+                message = m;
+                continue 'simpler;
+            }
+        }
+        return (case, message);
+    }
+}
+
+struct Counterexample {
+    seed: u64,
+    case: Case,
+    message: String,
+}
+
+fn find_counterexample<P>(property: P) -> Option<Counterexample>
+where
+    P: Fn(&Case) -> Result<(), String>,
+{
+    (SEED..SEED + CASES).find_map(|seed| {
+        let case = Case::generate(&mut StdRng::seed_from_u64(seed));
+        let message = property(&case).err()?;
+        let (case, message) = shrink(case, message, &property);
+        Some(Counterexample {
+            seed,
+            case,
+            message,
+        })
+    })
+}
+
+fn check<P>(property: P)
+where
+    P: Fn(&Case) -> Result<(), String>,
+{
+    if let Some(failure) = find_counterexample(property) {
+        panic!(
+            "seed {:#x}: {:?}\n{}",
+            failure.seed, failure.case, failure.message
+        );
+    }
+}
+
+fn same<T: PartialEq + std::fmt::Debug>(actual: T, expected: T) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("  actual: {actual:?}\nexpected: {expected:?}"))
+    }
+}
+
+#[test]
+fn str_delimiter_matches_split() {
+    check(|case| {
+        let Case {
+            haystack,
+            delimiter,
+            limit,
+        } = case;
+        match *limit {
+            None => same(
+                StrTok::new(haystack, delimiter.as_str()).collect::<Vec<_>>(),
+                haystack.split(delimiter.as_str()).collect(),
+            ),
+            Some(n) => same(
+                StrTok::new(haystack, delimiter.as_str())
+                    .limit(n)
+                    .collect::<Vec<_>>(),
+                haystack.splitn(n, delimiter.as_str()).collect(),
+            ),
+        }
+    });
+}
+
+#[test]
+fn char_delimiter_matches_split_both_ways() {
+    check(|case| {
+        let Some(c) = case.delimiter.chars().next() else {
+            return Ok(());
+        };
+        let haystack = &case.haystack;
+        same(
+            StrTok::new(haystack, &c).collect::<Vec<_>>(),
+            haystack.split(c).collect(),
+        )?;
+        same(
+            StrTok::new(haystack, &c).rev().collect::<Vec<_>>(),
+            haystack.rsplit(c).collect(),
+        )
+    });
+}
+
+#[test]
+fn shrinks_to_a_minimal_case() {
+    // A false property: no token ever contains a `b`.
+    let failure = find_counterexample(|case| {
+        let tokens: Vec<&str> = StrTok::new(&case.haystack, case.delimiter.as_str()).collect();
+        match tokens.iter().find(|token| token.contains('b')) {
+            Some(token) => Err(format!("{token:?} contains a b")),
+            None => Ok(()),
+        }
+    })
+    .expect("the property is false");
+
+    assert_eq!(failure.case.haystack, "b");
+    assert_eq!(failure.case.limit, None);
+    assert_eq!(failure.message, r#""b" contains a b"#);
+}
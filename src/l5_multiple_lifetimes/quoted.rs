@@ -0,0 +1,287 @@
+use std::borrow::Cow;
+use std::iter::FusedIterator;
+
+use super::{Delimiter, StrTok};
+
+/**
+ * Quote- and escape-aware tokenization.
+ *
+ * Delimiters inside a quoted section (opened and closed by the same quote
+ * character) do not split, and the escape character takes the next character
+ * literally, both inside and outside quotes. Quotes and escapes are removed from
+ * the tokens.
+ *
+ * Tokens are `Cow<'haystack, str>`: a field that is a single contiguous slice of
+ * the haystack (`fox`, `"New York"`, `\"`) stays borrowed, and only fields that
+ * have to be stitched together (`New\ York`, `"a""b"`) allocate.
+ */
+pub struct Quoted<'haystack, 'delim, 'quote, D: ?Sized = str> {
+    tokens: StrTok<'haystack, 'delim, D>,
+    quotes: &'quote [char],
+    escape: Option<char>,
+    doubled_quotes: bool,
+}
+
+impl<'haystack, 'delim, D: Delimiter + ?Sized> StrTok<'haystack, 'delim, D> {
+    /// Switches to quote-aware tokenization with the given quote characters and
+    /// `\` as the escape character.
+    ///
+    /// A [`limit`](StrTok::limit) set before carries over: the last token is
+    /// the rest of the haystack as is, quotes and escapes included.
+    pub fn quoted<'quote>(self, quotes: &'quote [char]) -> Quoted<'haystack, 'delim, 'quote, D> {
+        Quoted {
+            tokens: self,
+            quotes,
+            escape: Some('\\'),
+            doubled_quotes: false,
+        }
+    }
+}
+
+impl<D: Delimiter + ?Sized> Quoted<'_, '_, '_, D> {
+    /// Replaces the escape character; `None` disables escaping.
+    pub fn escape(mut self, escape: Option<char>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Whether a doubled quote inside a quoted section stands for one literal
+    /// quote, as in CSV (`"a""b"` is `a"b`), instead of closing and reopening
+    /// the section (`ab`).
+    pub fn doubled_quotes(mut self, doubled: bool) -> Self {
+        self.doubled_quotes = doubled;
+        self
+    }
+
+    fn is_special(&self, c: char) -> bool {
+        self.quotes.contains(&c) || Some(c) == self.escape
+    }
+}
+
+impl<'haystack, D: Delimiter + ?Sized> Quoted<'haystack, '_, '_, D> {
+    /// Appends the escaped character after the escape at `at`, returning the
+    /// offset just past it.
+    fn unescape(&self, at: usize, token: &mut Cow<'haystack, str>) -> usize {
+        let haystack = self.tokens.haystack;
+        let from = at + self.escape.map_or(0, char::len_utf8);
+        let width = haystack[from..self.tokens.cursor.end]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        append(token, &haystack[from..from + width]);
+        from + width
+    }
+
+    /// Appends the quoted section opened by `quote` just before `from`,
+    /// returning the offset just past the closing quote. An unterminated quote
+    /// runs to the end of the haystack.
+    fn quoted_section(&self, quote: char, from: usize, token: &mut Cow<'haystack, str>) -> usize {
+        let haystack = self.tokens.haystack;
+        let end = self.tokens.cursor.end;
+        let mut segment = from;
+        loop {
+            let special = haystack[segment..end]
+                .char_indices()
+                .find(|&(_, c)| c == quote || Some(c) == self.escape)
+                .map(|(i, c)| (segment + i, c));
+            match special {
+                None => {
+                    append(token, &haystack[segment..end]);
+                    return end;
+                }
+                Some((at, c)) if c == quote => {
+                    let after = at + c.len_utf8();
+                    if self.doubled_quotes && haystack[after..end].starts_with(quote) {
+                        append(token, &haystack[segment..after]);
+                        segment = after + c.len_utf8();
+                        continue;
+                    }
+                    append(token, &haystack[segment..at]);
+                    return after;
+                }
+                Some((at, _)) => {
+                    append(token, &haystack[segment..at]);
+                    segment = self.unescape(at, token);
+                }
+            }
+        }
+    }
+}
+
+/// Appends `piece` to `token`, staying borrowed for as long as the token is a
+/// single slice of the haystack.
+fn append<'haystack>(token: &mut Cow<'haystack, str>, piece: &'haystack str) {
+    if piece.is_empty() {
+        return;
+    }
+    match token {
+        Cow::Borrowed("") => *token = Cow::Borrowed(piece),
+        _ => token.to_mut().push_str(piece),
+    }
+}
+
+impl<'haystack, D: Delimiter + ?Sized> Iterator for Quoted<'haystack, '_, '_, D> {
+    type Item = Cow<'haystack, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.tokens.cursor.finished {
+            return None;
+        }
+        let haystack = self.tokens.haystack;
+        if !self.tokens.cursor.charge() {
+            return Some(Cow::Borrowed(&haystack[self.tokens.cursor.remainder()]));
+        }
+        let end = self.tokens.cursor.end;
+        let mut token = Cow::Borrowed("");
+        let mut segment = self.tokens.cursor.start;
+        let mut search = self.tokens.cursor.position;
+        loop {
+            let delimiter = if search <= end {
+                self.tokens
+                    .delimiter
+                    .find(&haystack[search..end])
+                    .map(|m| search + m.start..search + m.end)
+            } else {
+                None
+            };
+            let stop = delimiter.as_ref().map_or(end, |m| m.start);
+            let special = haystack[segment..stop]
+                .char_indices()
+                .find(|&(_, c)| self.is_special(c))
+                .map(|(i, c)| (segment + i, c));
+
+            let Some((at, c)) = special else {
+                append(&mut token, &haystack[segment..stop]);
+                let cursor = &mut self.tokens.cursor;
+                match delimiter {
+                    Some(m) => {
+                        cursor.start = m.end;
+                        cursor.position = if m.is_empty() {
+                            m.end + haystack[m.end..].chars().next().map_or(1, char::len_utf8)
+                        } else {
+                            m.end
+                        };
+                    }
+                    None => cursor.finished = true,
+                }
+                return Some(token);
+            };
+
+            append(&mut token, &haystack[segment..at]);
+            segment = if Some(c) == self.escape {
+                self.unescape(at, &mut token)
+            } else {
+                self.quoted_section(c, at + c.len_utf8(), &mut token)
+            };
+            search = segment;
+        }
+    }
+}
+
+impl<D: Delimiter + ?Sized> FusedIterator for Quoted<'_, '_, '_, D> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_do_not_split() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"name "New York" 1946"#, &' ')
+            .quoted(&['"'])
+            .collect();
+
+        assert_eq!(tokens, ["name", "New York", "1946"]);
+        assert!(tokens.iter().all(|t| matches!(t, Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn configurable_quotes() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"'a b' "c d" e"#, &' ')
+            .quoted(&['\'', '"'])
+            .collect();
+        assert_eq!(tokens, ["a b", "c d", "e"]);
+
+        let tokens: Vec<Cow<'_, str>> =
+            StrTok::new(r#"'a b' "c d""#, &' ').quoted(&['"']).collect();
+        assert_eq!(tokens, ["'a", "b'", "c d"]);
+    }
+
+    #[test]
+    fn escapes_allocate_only_when_needed() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"New\ York \" "say \"hi\"""#, &' ')
+            .quoted(&['"'])
+            .collect();
+
+        assert_eq!(tokens, ["New York", "\"", "say \"hi\""]);
+        assert!(matches!(tokens[0], Cow::Owned(_)));
+        assert!(matches!(tokens[1], Cow::Borrowed(_)));
+        assert!(matches!(tokens[2], Cow::Owned(_)));
+    }
+
+    #[test]
+    fn adjacent_sections_are_joined() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"a"b c"d "" x"#, &' ')
+            .quoted(&['"'])
+            .collect();
+
+        assert_eq!(tokens, ["ab cd", "", "x"]);
+    }
+
+    #[test]
+    fn doubled_quotes() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#""a""b" "" """" "x"""#, &' ')
+            .quoted(&['"'])
+            .doubled_quotes(true)
+            .collect();
+
+        assert_eq!(tokens, ["a\"b", "", "\"", "x\""]);
+    }
+
+    #[test]
+    fn escape_can_be_disabled() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"C:\dir "C:\Program Files""#, &' ')
+            .quoted(&['"'])
+            .escape(None)
+            .collect();
+
+        assert_eq!(tokens, [r"C:\dir", r"C:\Program Files"]);
+    }
+
+    #[test]
+    fn unterminated_quote_runs_to_the_end() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"a "b c"#, &' ').quoted(&['"']).collect();
+
+        assert_eq!(tokens, ["a", "b c"]);
+    }
+
+    #[test]
+    fn limit_carries_over() {
+        let tokens: Vec<Cow<'_, str>> = StrTok::new("a b c d", &' ')
+            .limit(2)
+            .quoted(&['"'])
+            .collect();
+        assert_eq!(tokens, ["a", "b c d"]);
+
+        let tokens: Vec<Cow<'_, str>> = StrTok::new(r#"set "New York" to "a b""#, &' ')
+            .limit(3)
+            .quoted(&['"'])
+            .collect();
+        assert_eq!(tokens, ["set", "New York", r#"to "a b""#]);
+
+        let tokens: Vec<Cow<'_, str>> = StrTok::new("a b", &' ').limit(0).quoted(&['"']).collect();
+        assert!(tokens.is_empty());
+    }
+
+    #[test]
+    fn unquoted_input_matches_plain_tokenization() {
+        for haystack in ["Quick brown fox", "Quick brown fox ", " ", "", "a,,b"] {
+            for delimiter in [" ", ",", ""] {
+                let quoted: Vec<Cow<'_, str>> =
+                    StrTok::new(haystack, delimiter).quoted(&['"']).collect();
+                let plain: Vec<&str> = StrTok::new(haystack, delimiter).collect();
+
+                assert_eq!(quoted, plain, "{haystack:?} / {delimiter:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use super::{split_once_at, Delimiter};
+
+/**
+ * A zero-copy cursor over a `&'a str`.
+ *
+ * Everything the scanner hands out is a slice of the input, typed `&'a str`
+ * rather than borrowing the scanner: `Scanner<'a>` is just a position plus a
+ * borrow it passes through, so the slices outlive the scanner itself.
+ */
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+/// A saved position to [`Scanner::rewind`] to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// What [`Scanner::expect`] wanted to see, and where.
+///
+/// It borrows the expected literal, not the input, so the error can outlive
+/// the text that failed to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Expected<'p> {
+    pub expected: &'p str,
+    pub position: usize,
+}
+
+impl fmt::Display for Expected<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {:?} at byte {}", self.expected, self.position)
+    }
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Scanner { input, position: 0 }
+    }
+
+    /// The input not scanned yet.
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// Takes everything up to (not including) the first match of `delimiter`,
+    /// or the rest of the input if there is none.
+    pub fn take_until<D: Delimiter + ?Sized>(&mut self, delimiter: &D) -> &'a str {
+        let (taken, _) = split_once_at(self.rest(), delimiter);
+        self.position += taken.len();
+        taken
+    }
+
+    pub fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        self.take_until(&|c: char| !pred(c))
+    }
+
+    pub fn skip_while(&mut self, pred: impl Fn(char) -> bool) {
+        self.take_while(pred);
+    }
+
+    /// Consumes `literal` if the input continues with it.
+    pub fn expect<'p>(&mut self, literal: &'p str) -> Result<&'a str, Expected<'p>> {
+        if !self.rest().starts_with(literal) {
+            return Err(Expected {
+                expected: literal,
+                position: self.position,
+            });
+        }
+        let matched = &self.rest()[..literal.len()];
+        self.position += literal.len();
+        Ok(matched)
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.position)
+    }
+
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        assert!(
+            checkpoint.0 <= self.input.len() && self.input.is_char_boundary(checkpoint.0),
+            "checkpoint from another scanner"
+        );
+        self.position = checkpoint.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_outlive_the_scanner() {
+        let input = String::from("Seoul 1946");
+
+        let (name, year) = {
+            let mut scanner = Scanner::new(&input);
+            let name = scanner.take_until(&' ');
+            scanner.skip_while(char::is_whitespace);
+            (name, scanner.rest())
+        }; // the scanner is gone, the slices still borrow `input`
+
+        assert_eq!((name, year), ("Seoul", "1946"));
+    }
+
+    #[test]
+    fn peek_and_next_char() {
+        let mut scanner = Scanner::new("서울");
+
+        assert_eq!(scanner.peek(), Some('서'));
+        assert_eq!(scanner.next_char(), Some('서'));
+        assert_eq!(scanner.position(), '서'.len_utf8());
+        assert_eq!(scanner.next_char(), Some('울'));
+        assert_eq!(scanner.next_char(), None);
+        assert!(scanner.is_at_end());
+    }
+
+    #[test]
+    fn take_until_with_any_delimiter() {
+        let mut scanner = Scanner::new("key = value; next");
+
+        assert_eq!(scanner.take_until(" = "), "key");
+        assert_eq!(scanner.expect(" = "), Ok(" = "));
+        assert_eq!(scanner.take_until(&[';', '\n']), "value");
+        assert_eq!(scanner.take_until(&';'), "");
+        scanner.next_char();
+        assert_eq!(scanner.take_while(|c| !c.is_alphabetic()), " ");
+        assert_eq!(scanner.take_until(&';'), "next");
+        assert!(scanner.is_at_end());
+    }
+
+    #[test]
+    fn expect_reports_what_and_where() {
+        let mut scanner = Scanner::new("name: Seoul");
+        scanner.take_until(&':');
+
+        let error = scanner.expect(" = ").unwrap_err();
+
+        assert_eq!(
+            error,
+            Expected {
+                expected: " = ",
+                position: 4
+            }
+        );
+        assert_eq!(error.to_string(), r#"expected " = " at byte 4"#);
+        assert_eq!(scanner.position(), 4);
+    }
+
+    #[test]
+    fn checkpoint_and_rewind() {
+        let mut scanner = Scanner::new("1946-08-15");
+        let start = scanner.checkpoint();
+
+        let year = scanner.take_while(|c| c.is_ascii_digit());
+        if scanner.expect("/").is_err() {
+            scanner.rewind(start);
+        }
+
+        assert_eq!(year, "1946");
+        assert_eq!(scanner.rest(), "1946-08-15");
+    }
+
+    #[test]
+    #[should_panic(expected = "checkpoint from another scanner")]
+    fn foreign_checkpoint_is_rejected() {
+        let mut long = Scanner::new("a much longer input");
+        long.take_until(&'r');
+        let checkpoint = long.checkpoint();
+
+        Scanner::new("short").rewind(checkpoint);
+    }
+}
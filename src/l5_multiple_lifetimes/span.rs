@@ -0,0 +1,198 @@
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use super::{Delimiter, StrTok};
+
+/**
+ * Where a token sits in its haystack, as a byte range.
+ *
+ * A span does not borrow the haystack, so it can be stored in an error value
+ * and turned back into a position (or an underlined excerpt) later on.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 1-based line and column; the column counts `char`s, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// The span of `token`, which must be a slice of `haystack`.
+    pub fn of(haystack: &str, token: &str) -> Span {
+        let start = (token.as_ptr() as usize).wrapping_sub(haystack.as_ptr() as usize);
+        assert!(
+            start <= haystack.len() && token.len() <= haystack.len() - start,
+            "token is not a slice of the haystack"
+        );
+        Span {
+            start,
+            end: start + token.len(),
+        }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn line_col(&self, haystack: &str) -> LineCol {
+        let before = &haystack[..self.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        LineCol {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// The line containing the start of the span, followed by a line of `^`
+    /// under the span (cut at the end of that line).
+    pub fn underline(&self, haystack: &str) -> String {
+        let line_start = haystack[..self.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = haystack[self.start..]
+            .find('\n')
+            .map_or(haystack.len(), |i| self.start + i);
+        let line = haystack[line_start..line_end].trim_end_matches('\r');
+
+        let indent = haystack[line_start..self.start].chars().count();
+        let width = haystack[self.start..self.end.min(line_end)].chars().count();
+        format!("{line}\n{}{}", " ".repeat(indent), "^".repeat(width.max(1)))
+    }
+}
+
+/**
+ * Pairs each token of the inner iterator with its [`Span`] in the haystack.
+ *
+ * Works for any iterator handing out slices of `haystack`; the span is
+ * recovered from where the slice points.
+ */
+pub struct Spanned<'haystack, I> {
+    haystack: &'haystack str,
+    tokens: I,
+}
+
+impl<'haystack, I> Spanned<'haystack, I>
+where
+    I: Iterator<Item = &'haystack str>,
+{
+    pub fn new(haystack: &'haystack str, tokens: I) -> Self {
+        Spanned { haystack, tokens }
+    }
+}
+
+impl<'haystack, 'delim, D: Delimiter + ?Sized> StrTok<'haystack, 'delim, D> {
+    /// Yields each token together with its [`Span`].
+    pub fn spanned(self) -> Spanned<'haystack, Self> {
+        Spanned::new(self.haystack, self)
+    }
+}
+
+impl<'haystack, I> Iterator for Spanned<'haystack, I>
+where
+    I: Iterator<Item = &'haystack str>,
+{
+    type Item = (Span, &'haystack str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        Some((Span::of(self.haystack, token), token))
+    }
+}
+
+impl<'haystack, I> DoubleEndedIterator for Spanned<'haystack, I>
+where
+    I: DoubleEndedIterator<Item = &'haystack str>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next_back()?;
+        Some((Span::of(self.haystack, token), token))
+    }
+}
+
+impl<'haystack, I> FusedIterator for Spanned<'haystack, I> where
+    I: FusedIterator<Item = &'haystack str>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_point_into_the_haystack() {
+        let haystack = "Quick brown  fox";
+        let spans: Vec<Range<usize>> = StrTok::new(haystack, &' ')
+            .spanned()
+            .map(|(span, _)| span.range())
+            .collect();
+
+        assert_eq!(spans, [0..5, 6..11, 12..12, 13..16]);
+        for (span, token) in StrTok::new(haystack, &' ').spanned() {
+            assert_eq!(&haystack[span.range()], token);
+        }
+    }
+
+    #[test]
+    fn spans_from_the_back() {
+        let haystack = "GET /index.html 404";
+        let (span, status) = StrTok::new(haystack, &' ').spanned().next_back().unwrap();
+
+        assert_eq!(status, "404");
+        assert_eq!(span, Span { start: 16, end: 19 });
+    }
+
+    #[test]
+    fn spans_of_any_slice_iterator() {
+        let haystack = "a=1;b=2";
+        let spans: Vec<Span> = Spanned::new(haystack, haystack.split(';'))
+            .map(|(span, _)| span)
+            .collect();
+
+        assert_eq!(
+            spans,
+            [Span { start: 0, end: 3 }, Span { start: 4, end: 7 }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not a slice of the haystack")]
+    fn foreign_token_is_rejected() {
+        let haystack = String::from("abc");
+        let other = String::from("abc");
+
+        Span::of(&haystack, &other);
+    }
+
+    #[test]
+    fn line_and_column() {
+        let haystack = "name Seoul\nfounded 1946\n서울 x";
+        let (span, _) = StrTok::new(haystack, &[' ', '\n'])
+            .spanned()
+            .find(|&(_, token)| token == "x")
+            .unwrap();
+
+        assert_eq!(span.line_col(haystack), LineCol { line: 3, column: 4 });
+
+        let (span, _) = StrTok::new(haystack, &[' ', '\n'])
+            .spanned()
+            .find(|&(_, token)| token == "1946")
+            .unwrap();
+
+        assert_eq!(span.line_col(haystack), LineCol { line: 2, column: 9 });
+    }
+
+    #[test]
+    fn underline_the_offending_token() {
+        let haystack = "name Seoul\r\nfounded 19x6\r\n";
+        let (span, _) = StrTok::new(haystack, &[' ', '\r', '\n'])
+            .spanned()
+            .find(|&(_, token)| token == "19x6")
+            .unwrap();
+
+        assert_eq!(span.underline(haystack), "founded 19x6\n        ^^^^");
+    }
+}
@@ -0,0 +1,199 @@
+use std::io::{self, BufRead};
+
+use super::ByteDelimiter;
+
+/**
+ * Tokenizes a `BufRead` without holding the whole input in memory.
+ *
+ * Only the current token (plus whatever the reader has buffered) is kept, so
+ * inputs larger than memory can be tokenized as long as single tokens fit. The
+ * semantics are `StrTok`'s: a trailing delimiter yields a final empty token and
+ * an empty input yields one empty token.
+ *
+ * Tokens come out two ways:
+ * - [`StreamTok::next_token`] lends a `&str` into the internal buffer, valid
+ *   until the next call; no allocation per token.
+ * - as an `Iterator` of owned `io::Result<String>`s.
+ */
+pub struct StreamTok<'delim, R> {
+    reader: R,
+    delimiter: &'delim str,
+    buf: Vec<u8>,
+    /// Bytes at the front of `buf` that belong to tokens already handed out.
+    consumed: usize,
+    eof: bool,
+    finished: bool,
+}
+
+impl<'delim, R: BufRead> StreamTok<'delim, R> {
+    pub fn new(reader: R, delimiter: &'delim str) -> Self {
+        assert!(
+            !delimiter.is_empty(),
+            "StreamTok needs a non-empty delimiter"
+        );
+        StreamTok {
+            reader,
+            delimiter,
+            buf: Vec::new(),
+            consumed: 0,
+            eof: false,
+            finished: false,
+        }
+    }
+
+    /// The next token, borrowed from the tokenizer until the next call.
+    ///
+    /// A token that is not valid UTF-8 is reported as `InvalidData` and skipped;
+    /// an error from the reader ends the stream.
+    pub fn next_token(&mut self) -> io::Result<Option<&str>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let delimiter = self.delimiter.as_bytes();
+        let mut start = self.consumed;
+        let mut scanned = start;
+        let end = loop {
+            if let Some(m) = delimiter.find(&self.buf[scanned..]) {
+                self.consumed = scanned + m.end;
+                break scanned + m.start;
+            }
+            // A delimiter may straddle the end of what has been read so far.
+            scanned = self
+                .buf
+                .len()
+                .saturating_sub(delimiter.len() - 1)
+                .max(start);
+            if self.eof {
+                self.finished = true;
+                self.consumed = self.buf.len();
+                break self.buf.len();
+            }
+
+            self.buf.drain(..start);
+            scanned -= start;
+            start = 0;
+            self.fill()?;
+        };
+        std::str::from_utf8(&self.buf[start..end])
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok([]) => self.eof = true,
+                Ok(chunk) => {
+                    let len = chunk.len();
+                    self.buf.extend_from_slice(chunk);
+                    self.reader.consume(len);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.finished = true;
+                    return Err(e);
+                }
+            }
+            return Ok(());
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for StreamTok<'_, R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token()
+            .map(|token| token.map(str::to_owned))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l5_multiple_lifetimes::tests::CASES;
+    use std::io::BufReader;
+
+    #[test]
+    fn same_tokens_as_split_with_tiny_buffers() {
+        for &(haystack, delimiter) in CASES {
+            if delimiter.is_empty() {
+                continue;
+            }
+            for capacity in 1..6 {
+                let reader = BufReader::with_capacity(capacity, haystack.as_bytes());
+                let tokens: Vec<String> = StreamTok::new(reader, delimiter)
+                    .collect::<io::Result<_>>()
+                    .unwrap();
+
+                assert_eq!(
+                    tokens,
+                    haystack.split(delimiter).collect::<Vec<_>>(),
+                    "{haystack:?} / {delimiter:?} / {capacity}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn delimiter_straddling_buffer_boundary() {
+        let reader = BufReader::with_capacity(4, "abc<|>def<|><|>".as_bytes());
+        let tokens: Vec<String> = StreamTok::new(reader, "<|>")
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(tokens, ["abc", "def", "", ""]);
+    }
+
+    #[test]
+    fn lending_tokens() -> io::Result<()> {
+        let reader = BufReader::with_capacity(2, "10 20 30".as_bytes());
+        let mut tokens = StreamTok::new(reader, " ");
+
+        let mut sum = 0;
+        while let Some(token) = tokens.next_token()? {
+            sum += token.parse::<u32>().unwrap();
+        }
+
+        assert_eq!(sum, 60);
+        Ok(())
+    }
+
+    #[cfg(feature = "skip")]
+    #[test]
+    fn lent_token_does_not_outlive_next_call() {
+        let mut tokens = StreamTok::new("a b".as_bytes(), " ");
+
+        let first = tokens.next_token().unwrap();
+        let second = tokens.next_token().unwrap(); // `tokens` is still borrowed by `first`
+        println!("{first:?} {second:?}");
+    }
+
+    #[test]
+    fn invalid_utf8_is_reported_and_skipped() {
+        let mut tokens = StreamTok::new(&b"ok \xff ok"[..], " ");
+
+        assert_eq!(tokens.next_token().unwrap(), Some("ok"));
+        let error = tokens.next_token().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(tokens.next_token().unwrap(), Some("ok"));
+        assert_eq!(tokens.next_token().unwrap(), None);
+    }
+
+    #[test]
+    fn reader_errors_end_the_stream() {
+        struct Failing;
+
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk on fire"))
+            }
+        }
+
+        let mut tokens = StreamTok::new(BufReader::new(Failing), " ");
+
+        assert!(tokens.next().unwrap().is_err());
+        assert!(tokens.next().is_none());
+    }
+}
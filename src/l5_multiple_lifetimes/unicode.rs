@@ -0,0 +1,487 @@
+//! Grapheme cluster and word boundaries, after UAX #29.
+//!
+//! The grapheme table in [`tables`] is generated from the Unicode 14.0 data by
+//! `tools/gen_unicode_tables.py`, and covers every script. Word_Break is
+//! derived from it where it can be (marks are `Extend`), with a hand-written
+//! table, also after Unicode 14.0, for the punctuation, spaces and letter
+//! classes the rules single out.
+//!
+//! Everything else that is alphabetic or numeric is `ALetter` or `Numeric` by
+//! `char::is_alphabetic` and `char::is_numeric`, which follow the standard
+//! library's own, usually newer, Unicode version (`char::UNICODE_VERSION`). So
+//! a letter added after 14.0 is still part of a word, but a mark added after
+//! 14.0 is `Other` for graphemes and starts a segment of its own.
+
+use std::cmp::Ordering;
+use std::iter::FusedIterator;
+
+mod tables;
+
+use tables::GRAPHEME;
+
+/// Where [`Segments`] cuts the haystack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Extended grapheme clusters: what a reader sees as one character.
+    Grapheme,
+    /// Word boundaries. Spaces and punctuation come out as segments of their
+    /// own; [`Words`] keeps only the words.
+    Word,
+}
+
+/**
+ * Tokenizes a haystack on Unicode boundaries instead of delimiters.
+ *
+ * Like `StrTok`, every segment is a `&'haystack str` slice of the input; no
+ * segment is ever empty, and together they cover the whole haystack.
+ */
+#[derive(Debug, Clone)]
+pub struct Segments<'haystack> {
+    haystack: &'haystack str,
+    boundary: Boundary,
+    front: usize,
+    back: usize,
+}
+
+impl<'haystack> Segments<'haystack> {
+    pub fn new(haystack: &'haystack str, boundary: Boundary) -> Self {
+        Segments {
+            haystack,
+            boundary,
+            front: 0,
+            back: haystack.len(),
+        }
+    }
+
+    fn is_boundary(&self, at: usize) -> bool {
+        match self.boundary {
+            Boundary::Grapheme => is_grapheme_boundary(self.haystack, at),
+            Boundary::Word => is_word_boundary(self.haystack, at),
+        }
+    }
+}
+
+impl<'haystack> Iterator for Segments<'haystack> {
+    type Item = &'haystack str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let start = self.front;
+        let end = self.haystack[start..self.back]
+            .char_indices()
+            .skip(1)
+            .map(|(i, _)| start + i)
+            .find(|&i| self.is_boundary(i))
+            .unwrap_or(self.back);
+        self.front = end;
+        Some(&self.haystack[start..end])
+    }
+}
+
+impl DoubleEndedIterator for Segments<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let end = self.back;
+        let start = self.haystack[self.front..end]
+            .char_indices()
+            .rev()
+            .map(|(i, _)| self.front + i)
+            .find(|&i| i > self.front && self.is_boundary(i))
+            .unwrap_or(self.front);
+        self.back = start;
+        Some(&self.haystack[start..end])
+    }
+}
+
+impl FusedIterator for Segments<'_> {}
+
+/// The words of a haystack: word-bounded segments holding at least one letter
+/// or digit.
+#[derive(Debug, Clone)]
+pub struct Words<'haystack> {
+    segments: Segments<'haystack>,
+}
+
+impl<'haystack> Words<'haystack> {
+    pub fn new(haystack: &'haystack str) -> Self {
+        Words {
+            segments: Segments::new(haystack, Boundary::Word),
+        }
+    }
+}
+
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+impl<'haystack> Iterator for Words<'haystack> {
+    type Item = &'haystack str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.segments.find(|segment| is_word(segment))
+    }
+}
+
+impl DoubleEndedIterator for Words<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.segments.rfind(|segment| is_word(segment))
+    }
+}
+
+impl FusedIterator for Words<'_> {}
+
+fn lookup<T: Copy>(table: &[(u32, u32, T)], c: char) -> Option<T> {
+    let c = c as u32;
+    table
+        .binary_search_by(|&(lo, hi, _)| {
+            if hi < c {
+                Ordering::Less
+            } else if lo > c {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .ok()
+        .map(|i| table[i].2)
+}
+
+/// Grapheme_Cluster_Break, with Extended_Pictographic folded in as one more
+/// value (no pictographic character has another break property).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gcb {
+    Other,
+    CR,
+    LF,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Pictographic,
+}
+
+fn gcb(c: char) -> Gcb {
+    match c {
+        '\r' => Gcb::CR,
+        '\n' => Gcb::LF,
+        // Precomposed Hangul syllables: every 28th one has no final consonant.
+        '\u{AC00}'..='\u{D7A3}' if (c as u32 - 0xAC00).is_multiple_of(28) => Gcb::Lv,
+        '\u{AC00}'..='\u{D7A3}' => Gcb::Lvt,
+        _ => lookup(GRAPHEME, c).unwrap_or(Gcb::Other),
+    }
+}
+
+fn is_grapheme_boundary(haystack: &str, at: usize) -> bool {
+    use Gcb::*;
+
+    let (before, after) = haystack.split_at(at);
+    let (Some(prev), Some(next)) = (before.chars().next_back(), after.chars().next()) else {
+        return true;
+    };
+    match (gcb(prev), gcb(next)) {
+        (CR, LF) => false,
+        (Control | CR | LF, _) | (_, Control | CR | LF) => true,
+        (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => false,
+        (_, Extend | Zwj | SpacingMark) | (Prepend, _) => false,
+        // An emoji ZWJ sequence: Pictographic Extend* ZWJ × Pictographic.
+        (Zwj, Pictographic) => {
+            let mut rest = before.chars().rev().skip(1).map(gcb);
+            rest.find(|&p| p != Extend) != Some(Pictographic)
+        }
+        // Flags: regional indicators pair up from the left.
+        (RegionalIndicator, RegionalIndicator) => {
+            let run = before
+                .chars()
+                .rev()
+                .take_while(|&c| gcb(c) == RegionalIndicator)
+                .count();
+            run % 2 == 0
+        }
+        _ => true,
+    }
+}
+
+/// Word_Break.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wb {
+    Other,
+    CR,
+    LF,
+    Newline,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Format,
+    Katakana,
+    HebrewLetter,
+    ALetter,
+    SingleQuote,
+    DoubleQuote,
+    MidNumLet,
+    MidLetter,
+    MidNum,
+    Numeric,
+    ExtendNumLet,
+    WSegSpace,
+}
+
+fn wb(c: char) -> Wb {
+    match gcb(c) {
+        Gcb::CR => return Wb::CR,
+        Gcb::LF => return Wb::LF,
+        Gcb::Extend | Gcb::SpacingMark => return Wb::Extend,
+        Gcb::Zwj => return Wb::Zwj,
+        Gcb::RegionalIndicator => return Wb::RegionalIndicator,
+        _ => {}
+    }
+    match lookup(WORD, c) {
+        Some(wb) => wb,
+        None if c.is_alphabetic() => Wb::ALetter,
+        None if c.is_numeric() => Wb::Numeric,
+        None => Wb::Other,
+    }
+}
+
+/// Characters that attach to whatever precedes them (WB4).
+fn is_ignored(wb: &Wb) -> bool {
+    matches!(wb, Wb::Extend | Wb::Format | Wb::Zwj)
+}
+
+fn is_word_boundary(haystack: &str, at: usize) -> bool {
+    use Wb::*;
+
+    let (before, after) = haystack.split_at(at);
+    let (Some(prev), Some(next)) = (before.chars().next_back(), after.chars().next()) else {
+        return true;
+    };
+    match (wb(prev), wb(next)) {
+        (CR, LF) => return false,
+        (Newline | CR | LF, _) | (_, Newline | CR | LF) => return true,
+        (Zwj, _) if gcb(next) == Gcb::Pictographic => return false,
+        (WSegSpace, WSegSpace) => return false,
+        (_, Extend | Format | Zwj) => return false,
+        _ => {}
+    }
+
+    // From here on, Extend, Format and ZWJ are invisible.
+    let mut back = before.chars().rev().map(wb).filter(|wb| !is_ignored(wb));
+    let mut ahead = after.chars().map(wb).filter(|wb| !is_ignored(wb));
+    let prev = back.next().unwrap_or(Other);
+    let before_prev = back.next();
+    let next = ahead.next().unwrap_or(Other);
+    let after_next = ahead.next();
+
+    let is_letter = |wb: Option<Wb>| matches!(wb, Some(ALetter | HebrewLetter));
+    match (prev, next) {
+        (ALetter | HebrewLetter, ALetter | HebrewLetter) => false,
+        (ALetter | HebrewLetter, MidLetter | MidNumLet | SingleQuote) => !is_letter(after_next),
+        (MidLetter | MidNumLet | SingleQuote, ALetter | HebrewLetter) => !is_letter(before_prev),
+        (HebrewLetter, SingleQuote) => false,
+        (HebrewLetter, DoubleQuote) => after_next != Some(HebrewLetter),
+        (DoubleQuote, HebrewLetter) => before_prev != Some(HebrewLetter),
+        (Numeric | ALetter | HebrewLetter, Numeric) | (Numeric, ALetter | HebrewLetter) => false,
+        (MidNum | MidNumLet | SingleQuote, Numeric) => before_prev != Some(Numeric),
+        (Numeric, MidNum | MidNumLet | SingleQuote) => after_next != Some(Numeric),
+        (Katakana, Katakana) => false,
+        (ALetter | HebrewLetter | Numeric | Katakana | ExtendNumLet, ExtendNumLet) => false,
+        (ExtendNumLet, ALetter | HebrewLetter | Numeric | Katakana) => false,
+        (RegionalIndicator, RegionalIndicator) => {
+            let run = before
+                .chars()
+                .rev()
+                .map(wb)
+                .filter(|wb| !is_ignored(wb))
+                .take_while(|&wb| wb == RegionalIndicator)
+                .count();
+            run % 2 == 0
+        }
+        _ => true,
+    }
+}
+
+/// Word_Break ranges, sorted, for what [`wb`] cannot derive from the grapheme
+/// table or from `char::is_alphabetic`/`char::is_numeric`. Ideographs, kana
+/// and scripts written without spaces are listed as `Other` so that they are
+/// not taken for `ALetter`.
+#[rustfmt::skip]
+static WORD: &[(u32, u32, Wb)] = {
+    use Wb::*;
+    &[
+        (0x000B, 0x000C, Newline), (0x0020, 0x0020, WSegSpace), (0x0022, 0x0022, DoubleQuote),
+        (0x0027, 0x0027, SingleQuote), (0x002C, 0x002C, MidNum), (0x002E, 0x002E, MidNumLet),
+        (0x003A, 0x003A, MidLetter), (0x003B, 0x003B, MidNum), (0x005F, 0x005F, ExtendNumLet),
+        (0x0085, 0x0085, Newline), (0x00AD, 0x00AD, Format), (0x00B7, 0x00B7, MidLetter),
+        (0x037E, 0x037E, MidNum), (0x0387, 0x0387, MidLetter), (0x055F, 0x055F, MidLetter),
+        (0x0589, 0x0589, MidNum), (0x05D0, 0x05EA, HebrewLetter), (0x05EF, 0x05F2, HebrewLetter),
+        (0x05F4, 0x05F4, MidLetter), (0x0600, 0x0605, Format), (0x060C, 0x060D, MidNum),
+        (0x061C, 0x061C, Format), (0x066C, 0x066C, MidNum), (0x06DD, 0x06DD, Format),
+        (0x070F, 0x070F, Format), (0x07F8, 0x07F8, MidNum),
+        (0x0E00, 0x0EFF, Other), (0x1000, 0x109F, Other), (0x1680, 0x1680, WSegSpace),
+        (0x1780, 0x17FF, Other),
+        (0x2000, 0x2006, WSegSpace), (0x2008, 0x200A, WSegSpace), (0x200E, 0x200F, Format),
+        (0x2018, 0x2019, MidNumLet), (0x2024, 0x2024, MidNumLet), (0x2027, 0x2027, MidLetter),
+        (0x2028, 0x2029, Newline), (0x202A, 0x202E, Format), (0x202F, 0x202F, ExtendNumLet),
+        (0x203F, 0x2040, ExtendNumLet), (0x2044, 0x2044, MidNum), (0x2054, 0x2054, ExtendNumLet),
+        (0x205F, 0x205F, WSegSpace), (0x2060, 0x2064, Format), (0x2066, 0x206F, Format),
+        (0x2E80, 0x2FFF, Other), (0x3000, 0x3000, WSegSpace), (0x3005, 0x3007, Other),
+        (0x3021, 0x3029, Other), (0x3031, 0x3035, Katakana), (0x3038, 0x303B, Other),
+        (0x3041, 0x3096, Other), (0x309B, 0x309C, Katakana), (0x309D, 0x309F, Other),
+        (0x30A0, 0x30FA, Katakana), (0x30FC, 0x30FF, Katakana), (0x31F0, 0x31FF, Katakana),
+        (0x32D0, 0x32FE, Katakana), (0x3300, 0x3357, Katakana),
+        (0x3400, 0x4DBF, Other), (0x4E00, 0x9FFF, Other), (0xF900, 0xFAFF, Other),
+        (0xFB1D, 0xFB1D, HebrewLetter), (0xFB1F, 0xFB28, HebrewLetter),
+        (0xFB2A, 0xFB4F, HebrewLetter),
+        (0xFE10, 0xFE10, MidNum), (0xFE13, 0xFE13, MidLetter), (0xFE14, 0xFE14, MidNum),
+        (0xFE33, 0xFE34, ExtendNumLet), (0xFE4D, 0xFE4F, ExtendNumLet), (0xFE50, 0xFE50, MidNum),
+        (0xFE52, 0xFE52, MidNumLet), (0xFE54, 0xFE54, MidNum), (0xFE55, 0xFE55, MidLetter),
+        (0xFEFF, 0xFEFF, Format), (0xFF07, 0xFF07, MidNumLet), (0xFF0C, 0xFF0C, MidNum),
+        (0xFF0E, 0xFF0E, MidNumLet), (0xFF1A, 0xFF1A, MidLetter), (0xFF1B, 0xFF1B, MidNum),
+        (0xFF3F, 0xFF3F, ExtendNumLet), (0xFF66, 0xFF9D, Katakana), (0xFFF9, 0xFFFB, Format),
+        (0x20000, 0x3FFFF, Other),
+    ]
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphemes(haystack: &str) -> Vec<&str> {
+        Segments::new(haystack, Boundary::Grapheme).collect()
+    }
+
+    fn word_bounds(haystack: &str) -> Vec<&str> {
+        Segments::new(haystack, Boundary::Word).collect()
+    }
+
+    #[test]
+    fn tables_are_sorted() {
+        fn check<T>(table: &[(u32, u32, T)]) {
+            for (i, &(lo, hi, _)) in table.iter().enumerate() {
+                assert!(lo <= hi, "{lo:#x}..{hi:#x}");
+                if let Some(&(next, _, _)) = table.get(i + 1) {
+                    assert!(hi < next, "{hi:#x} overlaps {next:#x}");
+                }
+            }
+        }
+        check(GRAPHEME);
+        check(WORD);
+    }
+
+    #[test]
+    fn std_unicode_is_at_least_as_new_as_the_tables() {
+        assert!(char::UNICODE_VERSION >= (14, 0, 0));
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        assert_eq!(graphemes(""), Vec::<&str>::new());
+        assert_eq!(graphemes("abc"), ["a", "b", "c"]);
+        assert_eq!(
+            graphemes("e\u{301}te\u{301}"),
+            ["e\u{301}", "t", "e\u{301}"]
+        );
+        assert_eq!(graphemes("a\r\nb\n\r"), ["a", "\r\n", "b", "\n", "\r"]);
+        assert_eq!(graphemes("\u{301}a"), ["\u{301}", "a"]);
+    }
+
+    #[test]
+    fn marks_in_every_script() {
+        assert_eq!(graphemes("நிலா"), ["நி", "லா"]);
+        assert_eq!(graphemes("ਕਿਤਾਬ"), ["ਕਿ", "ਤਾ", "ਬ"]);
+        assert_eq!(graphemes("ꦧꦱꦗꦮ"), ["ꦧ", "ꦱ", "ꦗ", "ꦮ"]);
+        assert_eq!(graphemes("ᬩᬲᬩᬮᬶ"), ["ᬩ", "ᬲ", "ᬩ", "ᬮᬶ"]);
+    }
+
+    #[test]
+    fn hangul() {
+        assert_eq!(graphemes("한국어"), ["한", "국", "어"]);
+        // Conjoining jamo: 한 as L V T, then 가 as L V.
+        assert_eq!(
+            graphemes("\u{1112}\u{1161}\u{11AB}\u{1100}\u{1161}"),
+            ["\u{1112}\u{1161}\u{11AB}", "\u{1100}\u{1161}"]
+        );
+    }
+
+    #[test]
+    fn emoji() {
+        assert_eq!(graphemes("👍🏽!"), ["👍🏽", "!"]);
+        assert_eq!(graphemes("👩‍💻👨‍👩‍👧"), ["👩‍💻", "👨‍👩‍👧"]);
+        assert_eq!(graphemes("🇰🇷🇯🇵🇺"), ["🇰🇷", "🇯🇵", "🇺"]);
+        assert_eq!(graphemes("a\u{200D}💻"), ["a\u{200D}", "💻"]);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        assert_eq!(
+            word_bounds("The quick (\"brown\")  fox"),
+            ["The", " ", "quick", " ", "(", "\"", "brown", "\"", ")", "  ", "fox"]
+        );
+        assert_eq!(
+            word_bounds("can't 3.14 1,000 e.g. a_b"),
+            ["can't", " ", "3.14", " ", "1,000", " ", "e.g", ".", " ", "a_b"]
+        );
+        assert_eq!(word_bounds("cafe\u{301}s"), ["cafe\u{301}s"]);
+        assert_eq!(word_bounds("a\r\nb"), ["a", "\r\n", "b"]);
+        assert_eq!(word_bounds("🇰🇷🇯🇵"), ["🇰🇷", "🇯🇵"]);
+        assert_eq!(word_bounds("🇰🇷🇯🇵🇺"), ["🇰🇷", "🇯🇵", "🇺"]);
+        assert_eq!(word_bounds("நிலா பூ"), ["நிலா", " ", "பூ"]);
+    }
+
+    #[test]
+    fn words() {
+        let words: Vec<&str> =
+            Words::new("The quick (\"brown\") fox can't jump 32.3 feet, right?").collect();
+        assert_eq!(
+            words,
+            ["The", "quick", "brown", "fox", "can't", "jump", "32.3", "feet", "right"]
+        );
+
+        // Ideographs are words on their own; a run of katakana is one word.
+        let words: Vec<&str> = Words::new("東京タワーに行く").collect();
+        assert_eq!(words, ["東", "京", "タワー", "に", "行", "く"]);
+
+        let words: Vec<&str> = Words::new("안녕하세요, 세계!").rev().collect();
+        assert_eq!(words, ["세계", "안녕하세요"]);
+    }
+
+    #[test]
+    fn segments_borrow_and_cover_the_haystack() {
+        let haystack = String::from("Ünïcödé 👩‍💻 text, 한국어 🇰🇷!");
+
+        for boundary in [Boundary::Grapheme, Boundary::Word] {
+            let forward: Vec<&str> = Segments::new(&haystack, boundary).collect();
+            let mut backward: Vec<&str> = Segments::new(&haystack, boundary).rev().collect();
+            backward.reverse();
+
+            assert_eq!(forward, backward);
+            assert_eq!(forward.concat(), haystack);
+            let mut at = 0;
+            for segment in forward {
+                assert_eq!(segment.as_ptr(), haystack[at..].as_ptr());
+                at += segment.len();
+            }
+        }
+    }
+
+    #[test]
+    fn interleaved_from_both_ends() {
+        let mut segments = Segments::new("a👍🏽b", Boundary::Grapheme);
+
+        assert_eq!(segments.next_back(), Some("b"));
+        assert_eq!(segments.next(), Some("a"));
+        assert_eq!(segments.next_back(), Some("👍🏽"));
+        assert_eq!(segments.next(), None);
+        assert_eq!(segments.next_back(), None);
+    }
+}
@@ -0,0 +1,167 @@
+#!/usr/bin/env python3
+"""Generates src/l5_multiple_lifetimes/unicode/tables.rs.
+
+    python3 tools/gen_unicode_tables.py > src/l5_multiple_lifetimes/unicode/tables.rs
+
+The only input is the General_Category data in Python's `unicodedata` module,
+which must be Unicode 14.0.0 (CPython 3.11). Everything else UAX #29 builds
+Grapheme_Cluster_Break from (Other_Grapheme_Extend, Prepended_Concatenation_Mark,
+Indic_Syllabic_Category, Hangul_Syllable_Type, the SpacingMark exceptions, the
+unassigned default-ignorables) is short and copied below from the Unicode 14.0
+files named next to each list. Extended_Pictographic is a coarse, hand-made
+subset of emoji-data.txt: whole blocks where emoji-data.txt lists most of a
+block, exact ranges elsewhere.
+"""
+
+import sys
+import unicodedata
+
+VERSION = "14.0.0"
+if unicodedata.unidata_version != VERSION:
+    sys.exit(f"unicodedata is Unicode {unicodedata.unidata_version}, expected {VERSION}")
+
+
+def ranges(*spans):
+    return {c for span in spans for c in range(span[0], span[-1] + 1)}
+
+
+# PropList.txt, Other_Grapheme_Extend.
+OTHER_GRAPHEME_EXTEND = ranges(
+    (0x09BE,), (0x09D7,), (0x0B3E,), (0x0B57,), (0x0BBE,), (0x0BD7,), (0x0CC2,),
+    (0x0CD5, 0x0CD6), (0x0D3E,), (0x0D57,), (0x0DCF,), (0x0DDF,), (0x1B35,), (0x200C,),
+    (0x302E, 0x302F), (0xFF9E, 0xFF9F), (0x1133E,), (0x11357,), (0x114B0,), (0x114BD,),
+    (0x115AF,), (0x11930,), (0x1D165,), (0x1D16E, 0x1D172), (0xE0020, 0xE007F),
+)
+# emoji-data.txt, Emoji_Modifier.
+EMOJI_MODIFIER = ranges((0x1F3FB, 0x1F3FF))
+# UAX #29, table 2: SpacingMark additions and exceptions.
+SPACING_MARK_EXTRA = ranges((0x0E33,), (0x0EB3,))
+SPACING_MARK_EXCEPT = ranges(
+    (0x102B, 0x102C), (0x1038,), (0x1062, 0x1064), (0x1067, 0x106D), (0x1083,),
+    (0x1087, 0x108C), (0x108F,), (0x109A, 0x109C), (0x1A61,), (0x1A63, 0x1A64), (0xAA7B,),
+    (0xAA7D,), (0x11720, 0x11721),
+)
+# PropList.txt, Prepended_Concatenation_Mark.
+PREPENDED_CONCATENATION_MARK = ranges(
+    (0x0600, 0x0605), (0x06DD,), (0x070F,), (0x0890, 0x0891), (0x08E2,), (0x110BD,), (0x110CD,),
+)
+# IndicSyllabicCategory.txt, Consonant_Preceding_Repha and Consonant_Prefixed.
+PREPEND_CONSONANTS = ranges(
+    (0x0D4E,), (0x111C2, 0x111C3), (0x1193F,), (0x11941,), (0x11A3A,), (0x11A84, 0x11A89),
+    (0x11D46,),
+)
+# DerivedCoreProperties.txt, Default_Ignorable_Code_Point, unassigned ones.
+UNASSIGNED_DEFAULT_IGNORABLE = ranges(
+    (0x2065,), (0xFFF0, 0xFFF8), (0xE0000,), (0xE0002, 0xE001F), (0xE0080, 0xE00FF),
+    (0xE01F0, 0xE0FFF),
+)
+# HangulSyllableType.txt; the LV and LVT syllables are computed in `gcb`.
+HANGUL = {
+    **dict.fromkeys(ranges((0x1100, 0x115F), (0xA960, 0xA97C)), "L"),
+    **dict.fromkeys(ranges((0x1160, 0x11A7), (0xD7B0, 0xD7C6)), "V"),
+    **dict.fromkeys(ranges((0x11A8, 0x11FF), (0xD7CB, 0xD7FB)), "T"),
+}
+# emoji-data.txt, Extended_Pictographic (coarse; see above).
+PICTOGRAPHIC = ranges(
+    (0x00A9,), (0x00AE,), (0x203C,), (0x2049,), (0x2122,), (0x2139,), (0x2194, 0x2199),
+    (0x21A9, 0x21AA), (0x231A, 0x231B), (0x2328,), (0x23CF,), (0x23E9, 0x23F3),
+    (0x23F8, 0x23FA), (0x24C2,), (0x25AA, 0x25AB), (0x25B6,), (0x25C0,), (0x25FB, 0x25FE),
+    (0x2600, 0x27BF), (0x2934, 0x2935), (0x2B05, 0x2B07), (0x2B1B, 0x2B1C), (0x2B50,),
+    (0x2B55,), (0x3030,), (0x303D,), (0x3297,), (0x3299,), (0x1F000, 0x1F0FF),
+    (0x1F10D, 0x1F10F), (0x1F12F,), (0x1F16C, 0x1F171), (0x1F17E, 0x1F17F), (0x1F18E,),
+    (0x1F191, 0x1F19A), (0x1F1AD, 0x1F1E5), (0x1F201, 0x1F20F), (0x1F21A,), (0x1F22F,),
+    (0x1F232, 0x1F23A), (0x1F23C, 0x1F23F), (0x1F249, 0x1F3FA), (0x1F400, 0x1F53D),
+    (0x1F546, 0x1F64F), (0x1F680, 0x1F6FF), (0x1F774, 0x1F77F), (0x1F7D5, 0x1F7FF),
+    (0x1F80C, 0x1F80F), (0x1F848, 0x1F84F), (0x1F85A, 0x1F85F), (0x1F888, 0x1F88F),
+    (0x1F8AE, 0x1F8FF), (0x1F90C, 0x1F93A), (0x1F93C, 0x1F945), (0x1F947, 0x1FAFF),
+    (0x1FC00, 0x1FFFD),
+)
+
+
+def gcb(c):
+    """The Grapheme_Cluster_Break of `c`, or None for Other (and for what
+    `gcb` in unicode.rs handles itself)."""
+    if c in (0x0D, 0x0A) or 0xAC00 <= c <= 0xD7A3:
+        return None
+    category = unicodedata.category(chr(c))
+    if c == 0x200D:
+        return "Zwj"
+    if 0x1F1E6 <= c <= 0x1F1FF:
+        return "RegionalIndicator"
+    if c in PREPENDED_CONCATENATION_MARK or c in PREPEND_CONSONANTS:
+        return "Prepend"
+    if c in HANGUL:
+        return HANGUL[c]
+    if category in ("Mn", "Me") or c in OTHER_GRAPHEME_EXTEND or c in EMOJI_MODIFIER:
+        return "Extend"
+    if (category == "Mc" or c in SPACING_MARK_EXTRA) and c not in SPACING_MARK_EXCEPT:
+        return "SpacingMark"
+    if category in ("Cc", "Cf", "Zl", "Zp") or c in UNASSIGNED_DEFAULT_IGNORABLE:
+        return "Control"
+    if c in PICTOGRAPHIC:
+        return "Pictographic"
+    return None
+
+
+def table():
+    rows = []
+    for c in range(0x110000):
+        value = gcb(c)
+        if rows and rows[-1][2] == value and rows[-1][1] == c - 1:
+            rows[-1][1] = c
+        else:
+            rows.append([c, c, value])
+    return [row for row in rows if row[2] is not None]
+
+
+HEADER = """\
+//! Grapheme_Cluster_Break, following its definition in UAX #29, generated by
+//! `tools/gen_unicode_tables.py` from the Unicode 14.0 data (see the script
+//! for its exact inputs):
+//!
+//! - `Prepend`: Prepended_Concatenation_Mark, plus the consonants
+//!   Indic_Syllabic_Category lists as preceding repha or prefixed.
+//! - `Extend`: Grapheme_Extend (every `Mn` and `Me` mark, plus
+//!   Other_Grapheme_Extend) and the emoji modifiers.
+//! - `SpacingMark`: every other `Mc` mark, plus U+0E33 and U+0EB3, minus the
+//!   handful of Myanmar, Tai Tham, Tai Viet and Ahom vowels UAX #29 excludes.
+//! - `Control`: `Cc`, `Cf`, `Zl` and `Zp`, and the unassigned default-ignorable
+//!   code points.
+//! - `L`, `V`, `T` from Hangul_Syllable_Type, regional indicators and ZWJ.
+//! - `Pictographic`: a coarse subset of Extended_Pictographic, whole blocks
+//!   where most of a block is pictographic.
+//!
+//! Code points not listed are `Other`. CR, LF and the precomposed Hangul
+//! syllables are left to `gcb`.
+
+use super::Gcb;
+
+/// Grapheme_Cluster_Break ranges, sorted and disjoint.
+#[rustfmt::skip]
+pub(super) static GRAPHEME: &[(u32, u32, Gcb)] = {
+    use Gcb::*;
+    &[
+"""
+
+FOOTER = """\
+    ]
+};
+"""
+
+
+def main():
+    out = [HEADER]
+    line = "       "
+    for lo, hi, value in table():
+        item = f" (0x{lo:04X}, 0x{hi:04X}, {value}),"
+        if len(line) + len(item) > 100:
+            out.append(line + "\n")
+            line = "       "
+        line += item
+    out.append(line + "\n")
+    out.append(FOOTER)
+    sys.stdout.write("".join(out))
+
+
+if __name__ == "__main__":
+    main()